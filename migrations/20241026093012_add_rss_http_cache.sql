@@ -0,0 +1,2 @@
+ALTER TABLE rss ADD COLUMN etag TEXT;
+ALTER TABLE rss ADD COLUMN last_modified TEXT;
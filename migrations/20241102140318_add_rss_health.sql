@@ -0,0 +1,9 @@
+CREATE TABLE rss_health
+(
+    rss_id               INTEGER PRIMARY KEY REFERENCES rss (id) ON DELETE CASCADE,
+    last_attempt         TEXT              NOT NULL,
+    last_success         TEXT,
+    consecutive_failures INTEGER DEFAULT 0 NOT NULL,
+    last_error           TEXT,
+    item_count           INTEGER
+);
@@ -0,0 +1,14 @@
+CREATE TABLE rss_item
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    rss_id      INTEGER NOT NULL REFERENCES rss (id) ON DELETE CASCADE,
+    guid        TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    season      INTEGER NOT NULL,
+    episode     INTEGER NOT NULL,
+    torrent_url TEXT    NOT NULL,
+    first_seen  TEXT    NOT NULL,
+    last_seen   TEXT    NOT NULL,
+    decision    TEXT    NOT NULL,
+    UNIQUE (rss_id, guid)
+);
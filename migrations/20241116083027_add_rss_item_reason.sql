@@ -0,0 +1,3 @@
+ALTER TABLE rss_item ADD COLUMN reason TEXT;
+ALTER TABLE rss_item ADD COLUMN filter TEXT;
+ALTER TABLE rss_item ADD COLUMN detail TEXT;
@@ -0,0 +1,8 @@
+CREATE TABLE global_filter
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    filter      TEXT NOT NULL,
+    description TEXT
+);
+
+ALTER TABLE rss ADD COLUMN skip_global_filters INTEGER DEFAULT 0 NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE rss ADD COLUMN wait_minutes INTEGER;
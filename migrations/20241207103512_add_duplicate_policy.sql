@@ -0,0 +1,2 @@
+ALTER TABLE rss ADD COLUMN duplicate_policy TEXT DEFAULT 'always' NOT NULL;
+ALTER TABLE download_task ADD COLUMN score INTEGER;
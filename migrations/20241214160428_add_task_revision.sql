@@ -0,0 +1 @@
+ALTER TABLE download_task ADD COLUMN revision INTEGER;
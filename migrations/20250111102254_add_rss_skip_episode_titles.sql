@@ -0,0 +1 @@
+ALTER TABLE rss ADD COLUMN skip_episode_titles INTEGER DEFAULT 0 NOT NULL;
@@ -0,0 +1,9 @@
+CREATE TABLE score_config
+(
+    id              INTEGER PRIMARY KEY CHECK (id = 1),
+    fansubs         TEXT NOT NULL,
+    blocked_fansubs TEXT NOT NULL,
+    resolutions     TEXT NOT NULL,
+    subtitles       TEXT NOT NULL,
+    codecs          TEXT NOT NULL
+);
@@ -3,7 +3,10 @@ use std::str::FromStr;
 use clap::{Parser, Subcommand};
 
 use bt::rss;
-use bt::rss::{parsers, RssType};
+use bt::rss::decision::ItemDecision;
+use bt::rss::filter::{sample_item, GlobalFilter, RssFilter, RssFilterChain};
+use bt::rss::score::parse_list;
+use bt::rss::{parsers, DuplicatePolicy, RssType};
 
 /// The RSS command to fetch and manage RSS feeds
 #[derive(Parser, Debug)]
@@ -25,8 +28,18 @@ enum RssCommands {
         ///
         /// ## Supported types
         /// - mikan(default)
+        /// - generic: RSS 2.0 or Atom feeds
+        /// - nyaa
+        /// - dmhy
+        /// - acg_rip
+        /// - torznab: Jackett or Prowlarr feeds
         #[arg(long, short, default_value = "mikan")]
         rss_type: String,
+
+        /// Explain whether each item would be downloaded and why, instead of
+        /// printing the items. The filters of the feed are applied if it's in the bt list.
+        #[arg(long)]
+        explain: bool,
     },
 
     /// Add a new RSS feed to the bt list
@@ -39,6 +52,11 @@ enum RssCommands {
         ///
         /// ## Supported types
         /// - mikan(default)
+        /// - generic: RSS 2.0 or Atom feeds
+        /// - nyaa
+        /// - dmhy
+        /// - acg_rip
+        /// - torznab: Jackett or Prowlarr feeds
         #[arg(long, short, default_value = "mikan")]
         rss_type: String,
 
@@ -50,24 +68,168 @@ enum RssCommands {
         /// Season of the rss feed, default to 1
         #[arg(long, short, default_value = "1")]
         season: Option<u64>,
+
+        /// Don't apply the global filters to the rss feed
+        #[arg(long)]
+        skip_global_filters: bool,
+
+        /// Don't name the files by the episode titles in the release names
+        #[arg(long)]
+        skip_episode_titles: bool,
+
+        /// Hold the releases of an episode for the minutes, then download the best one.
+        /// The releases are scored by the score config, see `score --help`.
+        #[arg(long)]
+        wait_minutes: Option<u64>,
+
+        /// What to do with an episode already downloaded, from any rss feed.
+        ///
+        /// ## Supported policies
+        /// - skip
+        /// - prefer_higher_score: download it again if the release scores higher
+        /// - always(default)
+        #[arg(long, default_value = "always")]
+        duplicate_policy: String,
+    },
+
+    /// Manage the global filters applied to every RSS feed
+    Filter {
+        #[command(subcommand)]
+        command: FilterCommands,
+    },
+
+    /// Manage the preferences to score the releases, shared by all the RSS feeds.
+    ///
+    /// The scores pick the best release in the wait window of a feed, and the releases
+    /// downloaded again with the `prefer_higher_score` duplicate policy. The blocked
+    /// fansubs are rejected on every feed not skipping the global filters.
+    Score {
+        #[command(subcommand)]
+        command: ScoreCommands,
+    },
+
+    /// Test filters against sample file names or a live feed, without saving them
+    TestFilter {
+        /// A filter to test, e.g. `FilenameRegex-\.mp4$` or `name ~ "1080p"`.
+        /// Repeat it to test a chain.
+        #[arg(long = "filter", short, value_name = "FILTER", required = true)]
+        filters: Vec<String>,
+
+        /// Sample file names to test the filters against
+        #[arg(value_name = "NAME", required_unless_present = "url")]
+        names: Vec<String>,
+
+        /// Url of a rss feed to test the filters against, instead of the names
+        #[arg(long, conflicts_with = "names")]
+        url: Option<String>,
+
+        /// Type of the rss feed parser, see `feed --help`
+        #[arg(long, short, default_value = "mikan")]
+        rss_type: String,
+    },
+
+    /// Search releases on a torznab indexer, e.g. Jackett or Prowlarr
+    Search {
+        /// Keywords to search
+        #[arg(value_name = "QUERY")]
+        query: String,
+
+        /// Torznab api url of the indexer, including the api key.
+        /// Read from `TORZNAB_URL` if not set.
+        #[arg(long, short)]
+        indexer: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum FilterCommands {
+    /// List the global filters
+    List,
+
+    /// Add a global filter
+    Add {
+        /// The filter, e.g. `FilenameRegex-\.mp4$` or `name ~ "Baha"`
+        #[arg(value_name = "FILTER")]
+        filter: String,
+
+        /// What the filter is for
+        #[arg(long, short)]
+        description: Option<String>,
+    },
+
+    /// Delete a global filter
+    Delete {
+        /// Id of the global filter
+        #[arg(value_name = "ID")]
+        id: i64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScoreCommands {
+    /// Show the score config
+    Show,
+
+    /// Update the score config, the lists not given are kept.
+    /// Each list is separated by commas and ordered from the most preferred.
+    Set {
+        /// Preferred fansubs, e.g. `LoliHouse,SweetSub`
+        #[arg(long)]
+        fansubs: Option<String>,
+
+        /// Fansubs never downloaded, e.g. `NC-Raws`
+        #[arg(long)]
+        blocked_fansubs: Option<String>,
+
+        /// Preferred resolutions, default to `2160p,1080p,720p,480p`
+        #[arg(long)]
+        resolutions: Option<String>,
+
+        /// Preferred subtitles made of `chs`, `cht`, `jpn`, `eng`, `soft` and `hard`,
+        /// joined by `+` when all of them are required, e.g. `chs+soft,chs,cht`
+        #[arg(long)]
+        subtitles: Option<String>,
+
+        /// Preferred codecs of `hevc`, `avc` and `av1`, e.g. `hevc,avc`
+        #[arg(long)]
+        codecs: Option<String>,
     },
 }
 
 pub async fn execute(subcommand: RssSubcommand) -> anyhow::Result<()> {
     match subcommand.command {
-        RssCommands::Feed { url, rss_type } => {
+        RssCommands::Feed {
+            url,
+            rss_type,
+            explain,
+        } => {
             let rss = rss::Rss::builder()
                 .url(url)
                 .rss_type(RssType::from_str(&rss_type)?)
                 .build();
-            let feeds = parsers::parse(&rss).await?;
-            println!("{:?}", feeds)
+            if explain {
+                let rss = rss::store::query_rss()
+                    .await?
+                    .into_iter()
+                    .find(|stored| stored.url == rss.url)
+                    .unwrap_or(rss);
+                for decision in rss::decision::explain(&rss).await? {
+                    print_decision(&decision);
+                }
+            } else {
+                let feeds = parsers::parse(&rss).await?;
+                println!("{:?}", feeds)
+            }
         }
         RssCommands::Add {
             url,
             rss_type,
             title,
             season,
+            skip_global_filters,
+            skip_episode_titles,
+            wait_minutes,
+            duplicate_policy,
         } => {
             let rss = rss::Rss::builder()
                 .url(url)
@@ -75,6 +237,10 @@ pub async fn execute(subcommand: RssSubcommand) -> anyhow::Result<()> {
                 .title(title)
                 .enabled(Some(true))
                 .season(season)
+                .skip_global_filters(skip_global_filters)
+                .skip_episode_titles(skip_episode_titles)
+                .wait_minutes(wait_minutes)
+                .duplicate_policy(DuplicatePolicy::from_str(&duplicate_policy)?)
                 .build();
             match rss::store::add_rss(&rss).await {
                 Err(e) => {
@@ -83,7 +249,127 @@ pub async fn execute(subcommand: RssSubcommand) -> anyhow::Result<()> {
                 _ => {}
             }
         }
+        RssCommands::Filter { command } => match command {
+            FilterCommands::List => {
+                for global_filter in rss::store::query_global_filters().await? {
+                    let description = global_filter.description.unwrap_or_default();
+                    println!(
+                        "{}\t{}\t{}",
+                        global_filter.id.unwrap_or_default(),
+                        global_filter.filter,
+                        description
+                    );
+                }
+            }
+            FilterCommands::Add {
+                filter,
+                description,
+            } => {
+                let global_filter = GlobalFilter::builder()
+                    .filter(RssFilter::from_str(&filter)?)
+                    .description(description)
+                    .build();
+                let id = rss::store::add_global_filter(&global_filter).await?;
+                println!("{}", id);
+            }
+            FilterCommands::Delete { id } => rss::store::delete_global_filter(id).await?,
+        },
+        RssCommands::Score { command } => match command {
+            ScoreCommands::Show => {
+                let config = rss::store::get_score_config().await?;
+                println!("fansubs\t{}", config.fansubs.join(","));
+                println!("blocked_fansubs\t{}", config.blocked_fansubs.join(","));
+                println!("resolutions\t{}", config.resolutions.join(","));
+                println!("subtitles\t{}", config.subtitles.join(","));
+                println!("codecs\t{}", config.codecs.join(","));
+            }
+            ScoreCommands::Set {
+                fansubs,
+                blocked_fansubs,
+                resolutions,
+                subtitles,
+                codecs,
+            } => {
+                let mut config = rss::store::get_score_config().await?;
+                let lists = [
+                    (fansubs, &mut config.fansubs),
+                    (blocked_fansubs, &mut config.blocked_fansubs),
+                    (resolutions, &mut config.resolutions),
+                    (subtitles, &mut config.subtitles),
+                    (codecs, &mut config.codecs),
+                ];
+                for (content, list) in lists {
+                    if let Some(content) = content {
+                        *list = parse_list(&content);
+                    }
+                }
+                rss::store::update_score_config(&config).await?;
+            }
+        },
+        RssCommands::TestFilter {
+            filters,
+            names,
+            url,
+            rss_type,
+        } => {
+            let filters = filters
+                .iter()
+                .map(|filter| RssFilter::from_str(filter))
+                .collect::<Result<Vec<_>, _>>()?;
+            let items = match url {
+                Some(url) => {
+                    let rss = rss::Rss::builder()
+                        .url(url)
+                        .rss_type(RssType::from_str(&rss_type)?)
+                        .build();
+                    parsers::parse(&rss).await?.items
+                }
+                None => names.iter().map(|name| sample_item(name)).collect(),
+            };
+            for result in RssFilterChain(filters).test(&items).await {
+                let verdict = if result.accepted {
+                    "accepted"
+                } else {
+                    "rejected"
+                };
+                match &result.filter {
+                    Some(filter) => println!("[{}] {} (filter: {})", verdict, result.name, filter),
+                    None => println!("[{}] {}", verdict, result.name),
+                }
+            }
+        }
+        RssCommands::Search { query, indexer } => {
+            let url = match indexer {
+                Some(url) => url,
+                None => std::env::var("TORZNAB_URL")
+                    .map_err(|_| anyhow::anyhow!("--indexer or TORZNAB_URL must be set"))?,
+            };
+            let rss = rss::Rss::builder()
+                .url(url)
+                .rss_type(RssType::Torznab)
+                .build();
+            let feeds = parsers::torznab::search(&rss, &query).await?;
+            println!("{:?}", feeds)
+        }
     }
 
     Ok(())
 }
+
+fn print_decision(decision: &ItemDecision) {
+    let episode = match (decision.season, decision.episode) {
+        (Some(season), Some(episode)) => format!(" S{:02}E{:02}", season, episode),
+        _ => "".to_string(),
+    };
+    let mut line = format!("[{}] {}{}", decision.reason, decision.title, episode);
+    if let Some(filter) = &decision.filter {
+        line.push_str(&format!(" (filter: {:?})", filter));
+    }
+    if let Some(score) = decision.score {
+        line.push_str(&format!(" (score: {})", score));
+    }
+    if let Some(detail) = &decision.detail {
+        line.push_str(&format!(" ({})", detail));
+    }
+    println!("{}", line);
+}
@@ -2,9 +2,12 @@ mod rss_api;
 
 use actix_http::body::MessageBody;
 use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::error::{InternalError, JsonPayloadError};
 use actix_web::http::StatusCode;
 use actix_web::middleware::Logger;
-use actix_web::{get, web, App, Error, HttpResponse, HttpServer, Responder, ResponseError};
+use actix_web::{
+    get, web, App, Error, HttpRequest, HttpResponse, HttpServer, Responder, ResponseError,
+};
 use bt::BTError;
 use log::info;
 pub use rss_api::*;
@@ -27,17 +30,33 @@ pub(crate) fn setup_app() -> App<
     >,
 > {
     let rss_scope = web::scope("/rss")
+        .app_data(web::JsonConfig::default().error_handler(json_error_handler))
         .service(web::resource("/preview").route(web::get().to(parse_rss)))
         .service(
             web::resource("")
                 .route(web::get().to(get_rss))
                 .route(web::post().to(add_rss)),
         )
+        // Registered before `/{id}` and `/filters/{id}`, which match them too.
+        .service(
+            web::resource("/filters")
+                .route(web::get().to(get_global_filters))
+                .route(web::post().to(add_global_filter)),
+        )
+        .service(web::resource("/filters/test").route(web::post().to(test_filters)))
+        .service(web::resource("/filters/{id}").route(web::delete().to(delete_global_filter)))
+        .service(
+            web::resource("/score")
+                .route(web::get().to(get_score_config))
+                .route(web::put().to(update_score_config)),
+        )
         .service(
             web::resource("/{id}")
                 .route(web::delete().to(delete_rss))
                 .route(web::put().to(update_rss)),
-        );
+        )
+        .service(web::resource("/{id}/items").route(web::get().to(get_rss_items)))
+        .service(web::resource("/{id}/explain").route(web::get().to(explain_rss)));
 
     App::new()
         .wrap(Logger::default())
@@ -59,6 +78,7 @@ pub(crate) enum ApiError {
 impl ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match self {
+            ApiError::InternalError(BTError::InvalidFilter(_)) => StatusCode::BAD_REQUEST,
             ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -69,4 +89,10 @@ impl ResponseError for ApiError {
     }
 }
 
+/// Respond to a malformed body with its error, e.g. the invalid filter of an rss.
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> Error {
+    let response = HttpResponse::BadRequest().json(err.to_string());
+    InternalError::from_response(err, response).into()
+}
+
 type ApiResult<T> = Result<T, ApiError>;
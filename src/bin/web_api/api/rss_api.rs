@@ -1,5 +1,8 @@
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use bt::rss::filter::{sample_item, GlobalFilter, RssFilterChain};
+use bt::rss::score::ScoreConfig;
 use bt::{renamer, rss, BTError};
+use serde::Deserialize;
 use serde_json::json;
 
 use super::ApiResult;
@@ -10,9 +13,7 @@ pub async fn get_rss() -> ApiResult<impl Responder> {
 }
 
 pub async fn add_rss(info: web::Json<rss::Rss>) -> ApiResult<impl Responder> {
-    let id = rss::store::add_rss(&info.into_inner())
-        .await
-        .map_err(BTError::from)?;
+    let id = rss::store::add_rss(&info.into_inner()).await?;
     Ok(web::Json(id))
 }
 
@@ -27,12 +28,91 @@ pub async fn update_rss(
     path: web::Path<i64>,
     info: web::Json<rss::Rss>,
 ) -> ApiResult<impl Responder> {
-    rss::store::update_rss(path.into_inner(), &info.into_inner())
+    rss::store::update_rss(path.into_inner(), &info.into_inner()).await?;
+    Ok(web::Json("ok"))
+}
+
+pub async fn get_global_filters() -> ApiResult<impl Responder> {
+    let filters = rss::store::query_global_filters()
+        .await
+        .map_err(BTError::from)?;
+    Ok(web::Json(filters))
+}
+
+pub async fn add_global_filter(info: web::Json<GlobalFilter>) -> ApiResult<impl Responder> {
+    let id = rss::store::add_global_filter(&info.into_inner()).await?;
+    Ok(web::Json(id))
+}
+
+pub async fn delete_global_filter(path: web::Path<i64>) -> ApiResult<impl Responder> {
+    rss::store::delete_global_filter(path.into_inner())
+        .await
+        .map_err(BTError::from)?;
+    Ok(web::Json("ok"))
+}
+
+pub async fn get_score_config() -> ApiResult<impl Responder> {
+    let config = rss::store::get_score_config()
+        .await
+        .map_err(BTError::from)?;
+    Ok(web::Json(config))
+}
+
+pub async fn update_score_config(info: web::Json<ScoreConfig>) -> ApiResult<impl Responder> {
+    rss::store::update_score_config(&info.into_inner())
         .await
         .map_err(BTError::from)?;
     Ok(web::Json("ok"))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FilterTestRequest {
+    filters: RssFilterChain,
+    /// Sample file names to test the filters against
+    #[serde(default)]
+    names: Vec<String>,
+    /// A feed to test the filters against, instead of the names
+    url: Option<String>,
+    rss_type: Option<rss::RssType>,
+}
+
+pub async fn test_filters(info: web::Json<FilterTestRequest>) -> ApiResult<impl Responder> {
+    let info = info.into_inner();
+    let items = match info.url {
+        Some(url) => {
+            let rss = rss::Rss::builder()
+                .url(url)
+                .rss_type(info.rss_type.unwrap_or(rss::RssType::Mikan))
+                .build();
+            rss::parsers::parse(&rss)
+                .await
+                .map_err(BTError::from)?
+                .items
+        }
+        None => info.names.iter().map(|name| sample_item(name)).collect(),
+    };
+    Ok(web::Json(info.filters.test(&items).await))
+}
+
+pub async fn get_rss_items(path: web::Path<i64>) -> ApiResult<impl Responder> {
+    let items = rss::store::query_rss_items(path.into_inner())
+        .await
+        .map_err(BTError::from)?;
+    Ok(web::Json(items))
+}
+
+pub async fn explain_rss(path: web::Path<i64>) -> ApiResult<impl Responder> {
+    let rss = rss::store::get_rss(path.into_inner())
+        .await
+        .map_err(BTError::from)?;
+    let Some(rss) = rss else {
+        return Ok(HttpResponse::NotFound().json("RSS not found"));
+    };
+
+    let decisions = rss::decision::explain(&rss).await?;
+    Ok(HttpResponse::Ok().json(decisions))
+}
+
 pub async fn parse_rss(req: HttpRequest) -> ApiResult<impl Responder> {
     let info = web::Query::<rss::Rss>::from_query(req.query_string());
     if info.is_err() {
@@ -4,6 +4,14 @@ use std::collections::HashMap;
 use serde_bencode::value::Value as BencodeValue;
 use sha1::Digest;
 
+/// A file in a torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentFile {
+    /// The path of the file, starting with the torrent name for multi-file torrents
+    pub path: String,
+    pub length: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct Torrent {
     _raw: Vec<u8>,
@@ -75,6 +83,90 @@ impl Torrent {
             _ => unreachable!(""),
         }
     }
+
+    /// The files in the torrent, without the padding files of hybrid torrents.
+    pub fn get_files(&self) -> Vec<TorrentFile> {
+        let Some(BencodeValue::Dict(info)) = self.get_info() else {
+            unreachable!()
+        };
+        let name = self.get_info_name();
+
+        match (info.get("files".as_bytes()), info.get("file tree".as_bytes())) {
+            (Some(BencodeValue::List(files)), _) => files
+                .iter()
+                .filter_map(|file| match file {
+                    BencodeValue::Dict(file) => Some(file),
+                    _ => None,
+                })
+                .filter(|file| match file.get("attr".as_bytes()) {
+                    Some(BencodeValue::Bytes(attr)) => !attr.contains(&b'p'),
+                    _ => true,
+                })
+                .map(|file| {
+                    let mut path = vec![name.clone()];
+                    if let Some(BencodeValue::List(parts)) = file.get("path".as_bytes()) {
+                        path.extend(parts.iter().filter_map(bencode_string));
+                    }
+                    TorrentFile {
+                        path: path.join("/"),
+                        length: bencode_length(file),
+                    }
+                })
+                .collect(),
+            (_, Some(BencodeValue::Dict(tree))) if !info.contains_key("length".as_bytes()) => {
+                let mut files = Vec::new();
+                walk_file_tree(tree, "", &mut files);
+                // The tree of a single file torrent only contains the file named as the torrent.
+                if files.len() != 1 || files[0].path != name {
+                    for file in &mut files {
+                        file.path = format!("{}/{}", name, file.path);
+                    }
+                }
+                files
+            }
+            _ => vec![TorrentFile {
+                path: name,
+                length: bencode_length(info),
+            }],
+        }
+    }
+}
+
+fn bencode_string(value: &BencodeValue) -> Option<String> {
+    match value {
+        BencodeValue::Bytes(bytes) => Some(String::from_utf8_lossy(bytes).to_string()),
+        _ => None,
+    }
+}
+
+fn bencode_length(dict: &HashMap<Vec<u8>, BencodeValue>) -> u64 {
+    match dict.get("length".as_bytes()) {
+        Some(BencodeValue::Int(length)) => *length as u64,
+        _ => 0,
+    }
+}
+
+/// Collect the files of a v2 `file tree`, where a file is a dict with an empty key.
+fn walk_file_tree(tree: &HashMap<Vec<u8>, BencodeValue>, dir: &str, files: &mut Vec<TorrentFile>) {
+    for (key, value) in tree {
+        let BencodeValue::Dict(value) = value else {
+            continue;
+        };
+        if key.is_empty() {
+            files.push(TorrentFile {
+                path: dir.to_string(),
+                length: bencode_length(value),
+            });
+        } else {
+            let key = String::from_utf8_lossy(key);
+            let path = if dir.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}/{}", dir, key)
+            };
+            walk_file_tree(value, &path, files);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +194,32 @@ mod tests {
 
         assert_eq!(hex::encode(info_hash), "03143c5aaf5545b9e54d221a3ef3f1671c51a9ef");
     }
+
+    #[test]
+    fn test_get_files() {
+        let dot_torrent =
+            std::fs::read("tests/dataset/872ab5abd72ea223d2a2e36688cc96f83bb71d42.torrent")
+                .unwrap();
+        let torrent = Torrent::from_bytes(&dot_torrent).unwrap();
+        assert_eq!(
+            torrent.get_files(),
+            vec![TorrentFile {
+                path: "[Nekomoe kissaten&LoliHouse] Sousou no Frieren - 17 [WebRip 1080p HEVC-10bit AAC ASSx2].mkv".to_string(),
+                length: 670863041,
+            }]
+        );
+
+        let dot_torrent =
+            std::fs::read("tests/dataset/bb95e3795d653b274dbc32e1c48d2d3543417156.torrent")
+                .unwrap();
+        let torrent = Torrent::from_bytes(&dot_torrent).unwrap();
+        assert_eq!(
+            torrent.get_files(),
+            vec![TorrentFile {
+                path: "迷宮飯 22/[TOC] Delicious in Dungeon [22][1080P][AVC AAC][CHT][MP4].mp4"
+                    .to_string(),
+                length: 760079352,
+            }]
+        );
+    }
 }
@@ -10,25 +10,44 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use typed_builder::TypedBuilder;
 
-use super::{TaskStatus, Torrent, TorrentInaccessibleError};
+use crate::http;
+
+use super::{Magnet, TaskStatus, Torrent, TorrentFile, TorrentInaccessibleError};
 
 /// The metadata of a torrent file
 #[derive(Debug, Clone, PartialEq, Eq, TypedBuilder, Default, Serialize, Deserialize)]
 pub struct TorrentMeta {
-    /// The url of the torrent file
+    /// The url of the torrent file, or a magnet link
     pub url: String,
     /// Download path
     #[builder(default)]
     pub save_path: Option<String>,
     #[builder(default)]
     pub category: Option<String>,
+    /// The info hash of the torrent, if the feed provides it
+    #[builder(default)]
+    pub hash: Option<String>,
 }
 
 #[allow(dead_code)]
 impl TorrentMeta {
+    /// The parsed magnet link, if the url is a magnet link.
+    pub fn magnet(&self) -> Option<Magnet> {
+        Magnet::parse(&self.url)
+    }
+
     async fn download_dot_torrent(&self) -> Result<Vec<u8>, TorrentInaccessibleError> {
         let url = &self.url;
-        let response = reqwest::get(url)
+        if url.starts_with("magnet:") {
+            // The metadata of a magnet link can only be fetched from the swarm.
+            return Err(TorrentInaccessibleError(
+                url.to_string(),
+                "Magnet links can't be downloaded as torrent files".to_string(),
+            ));
+        }
+        let client = http::client();
+        let response = client
+            .send(client.get(url))
             .await
             .map_err(|e| TorrentInaccessibleError(url.to_string(), e.to_string()))?;
         let content = response
@@ -60,14 +79,31 @@ impl TorrentMeta {
     }
 
     pub async fn get_torrent_id(&self) -> Result<String, TorrentInaccessibleError> {
+        if let Some(hash) = &self.hash {
+            return Ok(hash.to_lowercase());
+        }
+        if let Some(magnet) = self.magnet() {
+            return Ok(magnet.hash);
+        }
+
         let torrent = self.get_data().await?;
         Ok(hex::encode(&torrent.torrent_id()))
     }
 
     pub async fn get_name(&self) -> Result<String, TorrentInaccessibleError> {
+        if let Some(name) = self.magnet().and_then(|magnet| magnet.name) {
+            return Ok(name);
+        }
+
         let torrent = self.get_data().await?;
         Ok(torrent.get_info_name())
     }
+
+    /// The files in the torrent, which needs the torrent file even for magnet links.
+    pub async fn get_files(&self) -> Result<Vec<TorrentFile>, TorrentInaccessibleError> {
+        let torrent = self.get_data().await?;
+        Ok(torrent.get_files())
+    }
 }
 
 type TorrentCache = LruCache<String, Torrent>;
@@ -20,8 +20,9 @@ impl DummyDownloader {
 #[async_trait]
 impl Downloader for DummyDownloader {
     async fn download(&self, torrent: &TorrentMeta) -> Result<(), DownloaderError> {
-        let name = torrent.get_name().await.unwrap();
         let hash = torrent.get_torrent_id().await.unwrap();
+        // A magnet link without `dn` has no name until the metadata is fetched.
+        let name = torrent.get_name().await.unwrap_or_else(|_| hash.clone());
         let category = torrent.category.as_deref().unwrap_or("bangumi");
 
         let path = format!("./data/dummy/downloads/{}/{}", category, name);
@@ -58,6 +59,14 @@ impl Downloader for DummyDownloader {
     ) -> Result<(), DownloaderError> {
         unimplemented!()
     }
+
+    async fn remove(&self, torrent: &str, _delete_files: bool) -> Result<(), DownloaderError> {
+        let mut download_list_lock = self.download_list.lock().await;
+        {
+            download_list_lock.retain(|downloading| downloading.hash != torrent);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
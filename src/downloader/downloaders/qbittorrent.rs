@@ -114,6 +114,13 @@ impl Downloader for QBittorrentDownloader {
     ) -> Result<(), DownloaderError> {
         unimplemented!()
     }
+
+    async fn remove(&self, torrent: &str, delete_files: bool) -> Result<(), DownloaderError> {
+        self.api
+            .delete_torrents(vec![torrent.to_string()], delete_files)
+            .await
+            .map_err(|err| DownloaderError::ClientError(err.to_string()))
+    }
 }
 
 #[cfg(test)]
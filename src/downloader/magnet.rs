@@ -0,0 +1,120 @@
+use reqwest::Url;
+
+/// The parts of a magnet link we care about, parsed without any network I/O.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Magnet {
+    /// The lowercase hex info hash, as reported by the downloaders.
+    ///
+    /// For v2 only torrents (`urn:btmh:`), it's the sha256 hash truncated to 20 bytes.
+    pub hash: String,
+    /// The display name (`dn`) of the torrent.
+    pub name: Option<String>,
+}
+
+impl Magnet {
+    /// Parse a `magnet:?xt=urn:btih:...` or `magnet:?xt=urn:btmh:...` link.
+    ///
+    /// The btih hash can be either hex or base32 encoded. If a link contains both v1
+    /// and v2 hashes, the v1 hash wins.
+    pub fn parse(url: &str) -> Option<Magnet> {
+        let url = Url::parse(url.trim()).ok()?;
+        if url.scheme() != "magnet" {
+            return None;
+        }
+
+        let mut btih = None;
+        let mut btmh = None;
+        let mut name = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "xt" => {
+                    if let Some(hash) = value.strip_prefix("urn:btih:") {
+                        btih = btih.or_else(|| parse_btih(hash));
+                    } else if let Some(hash) = value.strip_prefix("urn:btmh:") {
+                        btmh = btmh.or_else(|| parse_btmh(hash));
+                    }
+                }
+                "dn" => name = Some(value.to_string()).filter(|name| !name.is_empty()),
+                _ => {}
+            }
+        }
+
+        Some(Magnet {
+            hash: btih.or(btmh)?,
+            name,
+        })
+    }
+}
+
+fn parse_btih(hash: &str) -> Option<String> {
+    match hash.len() {
+        40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(hash.to_lowercase()),
+        32 => decode_base32(hash).map(hex::encode),
+        _ => None,
+    }
+}
+
+/// The btmh hash is a multihash, only sha256 (`0x12`, 32 bytes long) is defined for torrents.
+fn parse_btmh(hash: &str) -> Option<String> {
+    let digest = hash.strip_prefix("1220")?;
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(digest[..40].to_lowercase())
+}
+
+/// Decode the RFC 4648 base32 alphabet without padding.
+fn decode_base32(content: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(content.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in content.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_magnet() {
+        let magnet = Magnet::parse(
+            "magnet:?xt=urn:btih:7F2B35C2A8E5B4A4D6F1E2C3B4A5968778695A4B&dn=Frieren+18&tr=http%3A%2F%2Fnyaa.tracker.wf%3A7777%2Fannounce",
+        )
+        .unwrap();
+        assert_eq!(magnet.hash, "7f2b35c2a8e5b4a4d6f1e2c3b4a5968778695a4b");
+        assert_eq!(magnet.name.as_deref(), Some("Frieren 18"));
+
+        let magnet = Magnet::parse("magnet:?xt=urn:btih:LKZW5VJQ7YAV3ZNXKJ2XN3GPQ4RWCPRX").unwrap();
+        assert_eq!(magnet.hash, "5ab36ed530fe015de5b7527576eccf8723613e37");
+        assert_eq!(magnet.name, None);
+
+        let magnet = Magnet::parse(
+            "magnet:?xt=urn:btmh:1220caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e&dn=bittorrent-v2-test",
+        )
+        .unwrap();
+        assert_eq!(magnet.hash, "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa");
+        assert_eq!(magnet.name.as_deref(), Some("bittorrent-v2-test"));
+    }
+
+    #[test]
+    fn test_parse_invalid_magnet() {
+        assert_eq!(Magnet::parse("https://nyaa.si/download/1771374.torrent"), None);
+        assert_eq!(Magnet::parse("magnet:?dn=Frieren+18"), None);
+        assert_eq!(Magnet::parse("magnet:?xt=urn:btih:1234"), None);
+        assert_eq!(Magnet::parse("magnet:?xt=urn:btih:LKZW5VJQ7YAV3ZNXKJ2XN3GPQ4RWCPR1"), None);
+    }
+}
@@ -1,6 +1,7 @@
 mod bittorrent;
 mod bittorrent_meta;
 mod downloaders;
+mod magnet;
 pub mod store;
 mod task;
 
@@ -19,6 +20,7 @@ use crate::DBError;
 
 pub use bittorrent::*;
 pub use bittorrent_meta::*;
+pub use magnet::Magnet;
 pub use task::*;
 
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +54,9 @@ pub trait Downloader: Send + Sync {
         old_path: &Path,
         new_path: &Path,
     ) -> Result<(), DownloaderError>;
+
+    /// Remove the torrent from the client, with its downloaded files if `delete_files`.
+    async fn remove(&self, torrent: &str, delete_files: bool) -> Result<(), DownloaderError>;
 }
 
 #[derive(Debug, Clone, EnumString)]
@@ -120,6 +125,8 @@ impl DownloadManager {
         rss_id: Option<i64>,
         torrent_meta: &TorrentMeta,
         bangumi_info: &BangumiInfo,
+        score: Option<i64>,
+        revision: Option<u64>,
     ) -> Result<(), DownloaderError> {
         if store::is_task_exist(&torrent_meta.url).await? {
             // Skip downloading if the torrent info already in the database .
@@ -149,6 +156,8 @@ impl DownloadManager {
                         .status(TaskStatus::Downloading)
                         .start_time(chrono::Local::now())
                         .renamed(false)
+                        .score(score)
+                        .revision(revision)
                         .build();
 
                     store::add_task(rss_id, &task, bangumi_info).await?;
@@ -159,6 +168,35 @@ impl DownloadManager {
         }
     }
 
+    /// Remove the tasks superseded by the revision of the release from the client, with
+    /// their files, if `DOWNLOADER_REMOVE_SUPERSEDED` is `true`.
+    ///
+    /// The library file is a hard link, so call it after the revised release replaces it.
+    pub async fn remove_superseded_tasks(
+        bangumi_info: &BangumiInfo,
+        revision: Option<u64>,
+    ) -> Result<(), DownloaderError> {
+        if !std::env::var("DOWNLOADER_REMOVE_SUPERSEDED").is_ok_and(|remove| remove == "true") {
+            return Ok(());
+        }
+
+        let downloader = Self::get_downloader().await;
+        let downloader_lock = downloader.lock().await;
+        for task in store::query_superseded_tasks(bangumi_info, revision).await? {
+            if task.status == TaskStatus::Removed {
+                continue;
+            }
+            info!("[downloader] Removing superseded task [{}]", task.torrent_hash);
+            match downloader_lock.remove(&task.torrent_hash, true).await {
+                Ok(()) => store::update_task_removed(&task.torrent_hash).await?,
+                Err(err) => {
+                    error!("[downloader] Failed to remove task [{}]: {:?}", task.torrent_hash, err)
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn get_downloader() -> Arc<Mutex<Box<dyn Downloader>>> {
         #[cfg(test)]
         {
@@ -285,6 +323,8 @@ mod tests {
                     .episode(1u64)
                     .category(None)
                     .build(),
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -295,4 +335,84 @@ mod tests {
         store::update_task_renamed(&torrent_hash).await.unwrap();
         assert_eq!(store::is_renamed(&torrent_hash).await.unwrap(), true);
     }
+
+    #[tokio::test]
+    async fn test_download_magnet() {
+        use crate::init;
+
+        init().await;
+
+        let downloader = DownloadManager::new().await;
+        let torrent = TorrentMeta::builder()
+            .url("magnet:?xt=urn:btih:LKZW5VJQ7YAV3ZNXKJ2XN3GPQ4RWCPRX&dn=Frieren+18".to_string())
+            .build();
+
+        downloader
+            .download_with_state(
+                None,
+                &torrent,
+                &BangumiInfo::builder()
+                    .show_name("Frieren".to_string())
+                    .episode_name(None)
+                    .display_name(None)
+                    .season(1u64)
+                    .episode(18u64)
+                    .category(None)
+                    .build(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let task = store::get_task("5ab36ed530fe015de5b7527576eccf8723613e37")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.torrent_url.as_deref(), Some(torrent.url.as_str()));
+        assert!(store::is_task_exist(&torrent.url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_remove_superseded_tasks() {
+        use crate::init;
+
+        init().await;
+
+        let info = BangumiInfo::builder()
+            .show_name("Dungeon Meshi".to_string())
+            .episode_name(None)
+            .display_name(Some("[LoliHouse] 1080p".to_string()))
+            .season(1u64)
+            .episode(7u64)
+            .category(None)
+            .build();
+        let gen_task = |torrent_hash: &str, revision| {
+            DownloadTask::builder()
+                .rss_id(None)
+                .torrent_hash(torrent_hash.to_string())
+                .torrent_url(None)
+                .status(TaskStatus::Completed)
+                .start_time(chrono::Local::now())
+                .renamed(true)
+                .revision(revision)
+                .build()
+        };
+        store::add_task(None, &gen_task("dungeon-meshi-07", None), &info)
+            .await
+            .unwrap();
+        store::add_task(None, &gen_task("dungeon-meshi-07v2", Some(2)), &info)
+            .await
+            .unwrap();
+
+        std::env::set_var("DOWNLOADER_REMOVE_SUPERSEDED", "true");
+        DownloadManager::remove_superseded_tasks(&info, Some(2))
+            .await
+            .unwrap();
+        let status = |torrent_hash| async move {
+            store::get_task(torrent_hash).await.unwrap().unwrap().status
+        };
+        assert_eq!(status("dungeon-meshi-07").await, TaskStatus::Removed);
+        assert_eq!(status("dungeon-meshi-07v2").await, TaskStatus::Completed);
+    }
 }
@@ -1,11 +1,32 @@
 use crate::downloader::{DownloadTask, TaskStatus};
 use crate::get_pool;
-use crate::renamer::BangumiInfo;
+use crate::renamer::{normalize_show_name, BangumiInfo};
 use log::{debug, info};
 use sqlx::query;
 use std::path::Path;
 use std::str::FromStr;
 
+/// Map a row of `download_task` to a [`DownloadTask`], the records of `query!` are of
+/// anonymous types.
+macro_rules! download_task {
+    ($rec:expr) => {{
+        let rec = $rec;
+        DownloadTask {
+            id: Some(rec.id),
+            rss_id: rec.rss_id,
+            torrent_hash: rec.torrent_hash,
+            torrent_url: rec.torrent_url,
+            start_time: chrono::DateTime::parse_from_rfc3339(&rec.start_time)
+                .unwrap()
+                .into(),
+            status: TaskStatus::from_str(&rec.status).unwrap(),
+            renamed: rec.renamed == 1,
+            score: rec.score,
+            revision: rec.revision.map(|revision| revision as u64),
+        }
+    }};
+}
+
 pub async fn add_task(
     rss_id: Option<i64>,
     task: &DownloadTask,
@@ -41,12 +62,19 @@ pub async fn add_task(
     let task_status = task.status.to_string();
     let season = bangumi_info.season as i64;
     let episode = bangumi_info.episode as i64;
+    let revision = task.revision.map(|revision| revision as i64);
+    let batch = bangumi_info.batch.map(|batch| batch.to_string());
+    let media_info = bangumi_info
+        .media_info
+        .as_ref()
+        .map(|media_info| serde_json::to_string(media_info).unwrap());
 
     let rec = query!(
         r#"
 INSERT INTO main.download_task (torrent_hash, torrent_url, rss_id, start_time, status,
-    show_name, episode_name, display_name, season, episode, category, renamed)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+    show_name, episode_name, display_name, season, episode, category, renamed, score, revision,
+    batch, special, media_info)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
 RETURNING id
         "#,
         task.torrent_hash,
@@ -61,6 +89,11 @@ RETURNING id
         episode,
         bangumi_info.category,
         task.renamed,
+        task.score,
+        revision,
+        batch,
+        bangumi_info.special,
+        media_info,
     )
     .fetch_one(pool)
     .await?;
@@ -81,6 +114,82 @@ pub async fn is_task_exist(torrent_url: &str) -> Result<bool, sqlx::Error> {
     Ok(rec.is_some())
 }
 
+/// The tasks of the same episode, from any rss or fansub, except the failed ones and
+/// the batches, as a batch release isn't a duplicate of an episode or the reverse.
+///
+/// Specials are the same episode only if their labels are the same too, as `SP1` and
+/// `OVA1` are both the episode 1 of season 0.
+///
+/// The show names are compared by [`BangumiInfo::normalized_show_name`].
+pub async fn query_episode_tasks(
+    bangumi_info: &BangumiInfo,
+) -> Result<Vec<DownloadTask>, sqlx::Error> {
+    let season = bangumi_info.season as i64;
+    let episode = bangumi_info.episode as i64;
+    let status_error = TaskStatus::Error.to_string();
+    let recs = query!(
+        r#"
+SELECT * FROM main.download_task
+WHERE season = ?1 AND episode = ?2 AND status != ?3 AND special IS ?4 AND batch IS NULL
+ORDER BY id ASC
+        "#,
+        season,
+        episode,
+        status_error,
+        bangumi_info.special,
+    )
+    .fetch_all(&get_pool().await)
+    .await?;
+
+    let show_name = bangumi_info.normalized_show_name();
+    let tasks = recs
+        .into_iter()
+        .filter(|rec| normalize_show_name(&rec.show_name) == show_name)
+        .map(|rec| download_task!(rec))
+        .collect();
+
+    Ok(tasks)
+}
+
+/// The tasks of the same release of the episode with a lower revision, e.g. the task of
+/// `19` from a fansub is superseded by `19v2` from the same fansub.
+///
+/// The releases are the same if their display names, made of the fansub and the media
+/// info, are the same, so they are linked to the same file in the library.
+pub async fn query_superseded_tasks(
+    bangumi_info: &BangumiInfo,
+    revision: Option<u64>,
+) -> Result<Vec<DownloadTask>, sqlx::Error> {
+    let season = bangumi_info.season as i64;
+    let episode = bangumi_info.episode as i64;
+    let status_error = TaskStatus::Error.to_string();
+    let recs = query!(
+        r#"
+SELECT * FROM main.download_task
+WHERE season = ?1 AND episode = ?2 AND status != ?3 AND display_name IS ?4
+    AND special IS ?5
+ORDER BY id ASC
+        "#,
+        season,
+        episode,
+        status_error,
+        bangumi_info.display_name,
+        bangumi_info.special,
+    )
+    .fetch_all(&get_pool().await)
+    .await?;
+
+    let show_name = bangumi_info.normalized_show_name();
+    let tasks = recs
+        .into_iter()
+        .filter(|rec| normalize_show_name(&rec.show_name) == show_name)
+        .map(|rec| download_task!(rec))
+        .filter(|task| task.is_superseded_by(revision))
+        .collect();
+
+    Ok(tasks)
+}
+
 pub async fn get_task(torrent_hash: &str) -> Result<Option<DownloadTask>, sqlx::Error> {
     let rec = query!(r#"SELECT * FROM main.download_task WHERE torrent_hash = ?1"#, torrent_hash)
         .fetch_optional(&get_pool().await)
@@ -88,17 +197,7 @@ pub async fn get_task(torrent_hash: &str) -> Result<Option<DownloadTask>, sqlx::
 
     match rec {
         None => return Ok(None),
-        Some(rec) => Ok(Some(DownloadTask {
-            id: Some(rec.id),
-            rss_id: rec.rss_id,
-            torrent_hash: rec.torrent_hash,
-            torrent_url: rec.torrent_url,
-            start_time: chrono::DateTime::parse_from_rfc3339(&rec.start_time)
-                .unwrap()
-                .into(),
-            status: TaskStatus::from_str(&rec.status).unwrap(),
-            renamed: rec.renamed == 1,
-        })),
+        Some(rec) => Ok(Some(download_task!(rec))),
     }
 }
 
@@ -112,20 +211,7 @@ pub async fn get_tasks_need_renamed() -> Result<Vec<DownloadTask>, sqlx::Error>
     .fetch_all(&get_pool().await)
     .await?;
 
-    let tasks = recs
-        .iter()
-        .map(|rec| DownloadTask {
-            id: Some(rec.id),
-            rss_id: rec.rss_id,
-            torrent_hash: rec.torrent_hash.clone(),
-            torrent_url: rec.torrent_url.clone(),
-            start_time: chrono::DateTime::parse_from_rfc3339(&rec.start_time)
-                .unwrap()
-                .into(),
-            status: TaskStatus::from_str(&rec.status).unwrap(),
-            renamed: rec.renamed == 1,
-        })
-        .collect();
+    let tasks = recs.into_iter().map(|rec| download_task!(rec)).collect();
 
     Ok(tasks)
 }
@@ -144,6 +230,11 @@ pub async fn get_bangumi_info(torrent_hash: &str) -> Result<Option<BangumiInfo>,
             season: rec.season.unwrap_or(1) as u64,
             episode: rec.episode.unwrap_or(1) as u64,
             category: rec.category,
+            batch: rec.batch.and_then(|batch| batch.parse().ok()),
+            special: rec.special,
+            media_info: rec
+                .media_info
+                .and_then(|media_info| serde_json::from_str(&media_info).ok()),
         })),
     }
 }
@@ -183,6 +274,20 @@ pub async fn update_task_renamed(torrent_hash: &str) -> Result<(), sqlx::Error>
     Ok(())
 }
 
+pub async fn update_task_removed(torrent_hash: &str) -> Result<(), sqlx::Error> {
+    let status_removed = TaskStatus::Removed.to_string();
+    query!(
+        r#"UPDATE main.download_task SET status = ?1 WHERE torrent_hash = ?2"#,
+        status_removed,
+        torrent_hash
+    )
+    .execute(&get_pool().await)
+    .await?;
+
+    info!("[store] Marked task [{}] removed.", torrent_hash);
+    Ok(())
+}
+
 pub async fn is_renamed(torrent_hash: &str) -> Result<bool, sqlx::Error> {
     let rec = query!(
         r#"SELECT renamed FROM main.download_task WHERE torrent_hash = ?1"#,
@@ -13,6 +13,20 @@ pub struct DownloadTask {
     pub start_time: DateTime<Local>,
     pub status: TaskStatus,
     pub renamed: bool,
+    /// The score of the release when it's downloaded, see [`crate::rss::score::ScoreConfig`]
+    #[builder(default)]
+    pub score: Option<i64>,
+    /// The revision of the release, `None` for the original release
+    #[builder(default)]
+    pub revision: Option<u64>,
+}
+
+impl DownloadTask {
+    /// Whether the task is superseded by a release with the revision, the original
+    /// release is the revision 1.
+    pub fn is_superseded_by(&self, revision: Option<u64>) -> bool {
+        self.revision.unwrap_or(1) < revision.unwrap_or(1)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
@@ -23,4 +37,6 @@ pub enum TaskStatus {
     Error,
     Downloading,
     Completed,
+    /// Removed from the client after a higher revision of the release replaced its files
+    Removed,
 }
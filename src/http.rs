@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::warn;
+use once_cell::sync::Lazy;
+use reqwest::{Method, RequestBuilder, Response, Url};
+
+/// The configuration of the shared http client.
+///
+/// ## Environment variables
+/// - `HTTP_CLIENT_PROXY`: proxy for all requests, e.g. `http://127.0.0.1:7890` or
+///   `socks5h://127.0.0.1:1080`
+/// - `HTTP_CLIENT_USER_AGENT`: the `User-Agent` header
+/// - `HTTP_CLIENT_TIMEOUT`: timeout of each request in seconds, default to 10
+/// - `HTTP_CLIENT_HOST_TIMEOUTS`: timeouts overriding the default one for some hosts,
+///   e.g. `mikanani.me=30,nyaa.si=20`
+/// - `HTTP_CLIENT_MAX_RETRIES`: retries on connection errors and 5xx responses, default to 3
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    pub timeout: Duration,
+    pub host_timeouts: HashMap<String, Duration>,
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled on each following retry
+    pub retry_backoff: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            user_agent: None,
+            timeout: Duration::from_secs(10),
+            host_timeouts: HashMap::new(),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl HttpConfig {
+    /// The invalid environment variables are ignored with a warning, keeping the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self {
+            proxy: std::env::var("HTTP_CLIENT_PROXY")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            user_agent: std::env::var("HTTP_CLIENT_USER_AGENT")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            ..Default::default()
+        };
+
+        if let Ok(timeout) = std::env::var("HTTP_CLIENT_TIMEOUT") {
+            match timeout.trim().parse() {
+                Ok(secs) => config.timeout = Duration::from_secs(secs),
+                Err(_) => {
+                    warn!("[http] Ignore HTTP_CLIENT_TIMEOUT, it must be seconds: {}", timeout)
+                }
+            }
+        }
+        if let Ok(host_timeouts) = std::env::var("HTTP_CLIENT_HOST_TIMEOUTS") {
+            match parse_host_timeouts(&host_timeouts) {
+                Some(host_timeouts) => config.host_timeouts = host_timeouts,
+                None => warn!(
+                    "[http] Ignore HTTP_CLIENT_HOST_TIMEOUTS, it must be like `host=seconds,...`: {}",
+                    host_timeouts
+                ),
+            }
+        }
+        if let Ok(max_retries) = std::env::var("HTTP_CLIENT_MAX_RETRIES") {
+            match max_retries.trim().parse() {
+                Ok(max_retries) => config.max_retries = max_retries,
+                Err(_) => warn!(
+                    "[http] Ignore HTTP_CLIENT_MAX_RETRIES, it must be a number: {}",
+                    max_retries
+                ),
+            }
+        }
+
+        config
+    }
+}
+
+/// Parse `host=seconds` pairs separated by commas.
+fn parse_host_timeouts(content: &str) -> Option<HashMap<String, Duration>> {
+    content
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (host, secs) = pair.split_once('=')?;
+            let secs = secs.trim().parse().ok()?;
+            Some((host.trim().to_lowercase(), Duration::from_secs(secs)))
+        })
+        .collect()
+}
+
+/// The http client shared by all the outgoing requests of bt.
+pub struct HttpClient {
+    client: reqwest::Client,
+    config: HttpConfig,
+}
+
+impl HttpClient {
+    pub fn new(config: HttpConfig) -> Result<Self, reqwest::Error> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            config,
+        })
+    }
+
+    /// Start a request with the timeout configured for the host of the url.
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let timeout = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .and_then(|host| self.config.host_timeouts.get(&host).copied())
+            .unwrap_or(self.config.timeout);
+        self.client.request(method, url).timeout(timeout)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Send the request, retrying with exponential backoff on connection errors,
+    /// timeouts and 5xx responses.
+    ///
+    /// The last response is returned as is if it still fails after all the retries.
+    /// Requests of non-idempotent methods like `POST` are sent only once, as they may
+    /// have been handled by the server before failing.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let idempotent = request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .is_some_and(|request| request.method().is_idempotent());
+        if !idempotent {
+            return request.send().await;
+        }
+
+        let mut backoff = self.config.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            let result = match request.try_clone() {
+                Some(request) => request.send().await,
+                // Streaming bodies can't be replayed.
+                None => return request.send().await,
+            };
+
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+            if !retryable || attempt >= self.config.max_retries {
+                return result;
+            }
+
+            let reason = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(err) => err.to_string(),
+            };
+            warn!("[http] Retry in {:?} ({}): {}", backoff, attempt + 1, reason);
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+static HTTP_CLIENT: Lazy<HttpClient> = Lazy::new(|| {
+    HttpClient::new(HttpConfig::from_env()).unwrap_or_else(|err| {
+        // e.g. an invalid proxy url or user agent
+        warn!("[http] Invalid http client config, use the default one: {}", err);
+        HttpClient::new(HttpConfig::default()).expect("Failed to build the http client")
+    })
+});
+
+/// The shared http client configured from the environment variables, see [`HttpConfig`].
+pub fn client() -> &'static HttpClient {
+    &HTTP_CLIENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{serve_http, StubResponse};
+    use reqwest::StatusCode;
+
+    fn test_client(max_retries: u32) -> HttpClient {
+        HttpClient::new(HttpConfig {
+            user_agent: Some("bt-test".to_string()),
+            max_retries,
+            retry_backoff: Duration::from_millis(1),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn stub(status: u16) -> StubResponse {
+        StubResponse {
+            status,
+            headers: vec![],
+            body: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_host_timeouts() {
+        let timeouts = parse_host_timeouts("mikanani.me=30, Nyaa.si=20,").unwrap();
+        assert_eq!(timeouts.len(), 2);
+        assert_eq!(timeouts["mikanani.me"], Duration::from_secs(30));
+        assert_eq!(timeouts["nyaa.si"], Duration::from_secs(20));
+
+        assert!(parse_host_timeouts("mikanani.me").is_none());
+        assert!(parse_host_timeouts("mikanani.me=soon").is_none());
+    }
+
+    #[test]
+    fn test_invalid_env() {
+        std::env::set_var("HTTP_CLIENT_TIMEOUT", "10s");
+        std::env::set_var("HTTP_CLIENT_HOST_TIMEOUTS", "mikanani.me");
+        std::env::set_var("HTTP_CLIENT_MAX_RETRIES", "-1");
+        let config = HttpConfig::from_env();
+        std::env::remove_var("HTTP_CLIENT_TIMEOUT");
+        std::env::remove_var("HTTP_CLIENT_HOST_TIMEOUTS");
+        std::env::remove_var("HTTP_CLIENT_MAX_RETRIES");
+
+        // The invalid values fall back to the defaults instead of panicking.
+        let default = HttpConfig::default();
+        assert_eq!(config.timeout, default.timeout);
+        assert!(config.host_timeouts.is_empty());
+        assert_eq!(config.max_retries, default.max_retries);
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_errors() {
+        let (base_url, requests) =
+            serve_http(vec![stub(502), stub(503), StubResponse::ok("ok")]).await;
+
+        let client = test_client(3);
+        let response = client.send(client.get(&base_url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "ok");
+
+        let requests = requests.await.unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].to_lowercase().contains("user-agent: bt-test"));
+    }
+
+    #[tokio::test]
+    async fn test_give_up_after_retries() {
+        let (base_url, requests) = serve_http(vec![stub(500), stub(500), stub(404)]).await;
+
+        let client = test_client(1);
+        let response = client.send(client.get(&base_url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        // Client errors are not retried.
+        let response = client.send(client.get(&base_url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        assert_eq!(requests.await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_post() {
+        let (base_url, requests) = serve_http(vec![stub(502)]).await;
+
+        let client = test_client(3);
+        let response = client
+            .send(client.post(&base_url).body("msg"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        assert_eq!(requests.await.unwrap().len(), 1);
+    }
+}
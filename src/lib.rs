@@ -5,12 +5,14 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::rss::decision::{decide_all, DecisionReason, ItemDecision};
 use crate::rss::parsers;
 use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::SqlitePool;
 use tokio::sync::{Mutex, OnceCell};
 
 pub mod downloader;
+pub mod http;
 pub mod notification;
 pub mod renamer;
 pub mod rss;
@@ -36,6 +38,9 @@ pub async fn init() {
         // Init logger
         env_logger::init();
 
+        // Build the http client now to warn about its invalid config on startup.
+        http::client();
+
         let pool = get_pool().await;
         sqlx::migrate!("./migrations")
             .run(&pool)
@@ -48,6 +53,8 @@ pub async fn init() {
 pub async fn download_rss_feeds(downloader: &DownloadManager) -> BTResult<()> {
     debug!("[rss] Fetching RSS feeds...");
     let rss_list = rss::store::query_rss().await.unwrap_or_default();
+    let global_filters = rss::store::query_global_filters().await?.into();
+    let score_config = rss::store::get_score_config().await?;
 
     for rss in rss_list {
         let rss_id = rss.id.expect("Rss id should not be None here.");
@@ -55,41 +62,141 @@ pub async fn download_rss_feeds(downloader: &DownloadManager) -> BTResult<()> {
             log_with!(debug, rss_id, "[rss] Skip disabled RSS: ({})", rss.url);
             continue;
         }
+        if let Some(next_attempt) = rss.health.as_ref().and_then(|h| h.next_attempt()) {
+            if next_attempt > chrono::Local::now() {
+                log_with!(
+                    debug,
+                    rss_id,
+                    "[rss] Skip failing RSS until {}: ({})",
+                    next_attempt,
+                    rss.url
+                );
+                continue;
+            }
+        }
 
-        match parsers::parse(&rss).await {
-            Ok(feeds) => {
+        match parsers::parse_if_modified(&rss).await {
+            Ok(None) => {
+                log_with!(debug, rss_id, "[rss] Skip unmodified RSS: ({})", rss.url);
+                record_fetch_result(rss_id, Ok(None)).await;
+            }
+            Ok(Some((feeds, http_cache))) => {
+                record_fetch_result(rss_id, Ok(Some(feeds.items.len() as u64))).await;
+
+                let mut last_decisions = Vec::new();
                 for feed in &feeds.items {
-                    // TODO: Rss filter should contains including type.
-                    // If the torrent files mismatch the filter rules, skip downloading
-                    if let Some(filter) = &rss.filters {
-                        if !filter.is_match(&feed).await {
-                            log_with!(info, rss_id, "[parser] Skip torrent by rules: {:?}", feed);
+                    let last_decision = rss::store::mark_item_seen(rss_id, feed)
+                        .await
+                        .map(Some)
+                        .unwrap_or_else(|e| {
+                            log_with!(error, rss_id, "[rss] Failed to record item: {:?}", e);
+                            None
+                        });
+                    last_decisions.push(last_decision);
+                }
+                let decisions =
+                    decide_all(&rss, &global_filters, &score_config, &feeds.items, &last_decisions)
+                        .await;
+                // Fetch the feed again until the waiting releases are decided.
+                let mut all_decided = !decisions
+                    .iter()
+                    .any(|decision| decision.reason == DecisionReason::Waiting);
+
+                for (feed, mut decision) in feeds.items.iter().zip(decisions) {
+                    if decision.accepted {
+                        if let Err(e) = downloader
+                            .download_with_state(
+                                Some(rss_id),
+                                &feed.torrent,
+                                &feed.into(),
+                                decision.score,
+                                feed.revision,
+                            )
+                            .await
+                        {
+                            all_decided = false;
+                            decision = decision.download_failed(e.to_string());
+                        }
+                        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                    }
+
+                    match decision.reason {
+                        DecisionReason::AlreadyProcessed => {
+                            log_with!(debug, rss_id, "[rss] Skip processed item: {}", feed.guid());
+                            continue;
+                        }
+                        DecisionReason::DownloadFailed => {
+                            log_with!(error, rss_id, "[rss] Failed to download: {:?}", decision);
+                        }
+                        _ => log_with!(info, rss_id, "[rss] {}: {:?}", decision.reason, decision),
+                    }
+                    rss::store::update_item_decision(rss_id, &decision)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log_with!(error, rss_id, "[rss] Failed to record decision: {:?}", e);
+                        });
+                }
+
+                for item in feeds.unparsed.iter().filter(|item| !item.guid.is_empty()) {
+                    match rss::store::mark_unparsed_item_seen(rss_id, item).await {
+                        // Keep the decision if the item was parsed by a previous poll.
+                        Ok(last_decision) if last_decision.is_final() => continue,
+                        Ok(_) => {}
+                        Err(e) => {
+                            log_with!(error, rss_id, "[rss] Failed to record item: {:?}", e);
                             continue;
                         }
                     }
+                    let decision = ItemDecision::unparsed(item);
+                    rss::store::update_item_decision(rss_id, &decision)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log_with!(error, rss_id, "[rss] Failed to record decision: {:?}", e);
+                        });
+                }
 
-                    downloader
-                        .download_with_state(Some(rss_id), &feed.torrent, &feed.into())
+                // Keep the old validators on failures, so the feed is fetched again next time.
+                if all_decided && http_cache != rss.http_cache {
+                    rss::store::update_http_cache(rss_id, &http_cache)
                         .await
                         .unwrap_or_else(|e| {
-                            log_with!(
-                                error,
-                                rss_id,
-                                "[parser] Failed to download torrent: {:?}",
-                                e
-                            );
+                            log_with!(error, rss_id, "[rss] Failed to update http cache: {:?}", e);
                         });
-                    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
                 }
             }
             Err(e) => {
                 log_with!(error, rss_id, "[parser] Failed to parse RSS: {:?}", e);
+                record_fetch_result(rss_id, Err(e.to_string())).await;
             }
         }
     }
     Ok(())
 }
 
+/// Update the fetch health of the rss, and disable it if it keeps failing.
+async fn record_fetch_result(rss_id: i64, result: Result<Option<u64>, String>) {
+    let res = match result {
+        Ok(item_count) => rss::store::record_fetch_success(rss_id, item_count).await,
+        Err(err) => match rss::store::record_fetch_failure(rss_id, &err).await {
+            Ok(failures) if failures >= rss::health::MAX_CONSECUTIVE_FAILURES => {
+                log_with!(
+                    error,
+                    rss_id,
+                    "[rss] Disable RSS after {} consecutive failures",
+                    failures
+                );
+                rss::store::disable_rss(rss_id).await
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        },
+    };
+
+    if let Err(e) = res {
+        log_with!(error, rss_id, "[rss] Failed to update health: {:?}", e);
+    }
+}
+
 pub async fn rename_downloaded_files(
     download_task: &DownloadingTorrent,
     archived_path: &str,
@@ -120,12 +227,26 @@ pub async fn rename_downloaded_files(
         remapped_src_path = renamer::replace_path(src_path.clone(), path_map);
     }
 
+    // A revised release replaces the files linked by the earlier one.
+    let revision = downloader::store::get_task(torrent_hash)
+        .await?
+        .and_then(|task| task.revision);
+    let revised = revision.is_some_and(|revision| revision > 1);
+
     match downloader::store::get_bangumi_info(&torrent_hash).await? {
         Some(info) => {
-            match renamer::rename(&info, &remapped_src_path, dst_folder) {
+            match renamer::rename(&info, &remapped_src_path, dst_folder, revised) {
                 Ok(()) => {
                     downloader::store::update_task_renamed(&torrent_hash).await?;
 
+                    if revised {
+                        DownloadManager::remove_superseded_tasks(&info, revision)
+                            .await
+                            .unwrap_or_else(|e| {
+                                error!("[rename] Failed to remove superseded tasks: {:?}", e);
+                            });
+                    }
+
                     // Send notification
                     if let Some(notifier) = notifier.as_ref() {
                         let msg = notification::Notification::DownloadFinished(info).to_string();
@@ -158,13 +279,26 @@ async fn init_db() -> DBResult<SqlitePool> {
     #[cfg(not(test))]
     let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     #[cfg(test)]
-    let url = "sqlite::memory:".to_string();
+    let url = "sqlite:file:bt-test?mode=memory&cache=shared".to_string();
 
     let options = SqliteConnectOptions::from_str(&url)?.create_if_missing(true);
+    #[cfg(test)]
+    keep_test_db_alive(&options).await?;
     let pool = SqlitePool::connect_with(options).await?;
     Ok(pool)
 }
 
+/// The in-memory database of the tests is dropped with its last connection, and the pooled
+/// connections are closed with the runtime of the test using them, so hold one apart.
+#[cfg(test)]
+async fn keep_test_db_alive(options: &SqliteConnectOptions) -> DBResult<()> {
+    static TEST_DB_CONNECTION: OnceCell<Mutex<sqlx::SqliteConnection>> = OnceCell::const_new();
+
+    let connection = sqlx::ConnectOptions::connect(options).await?;
+    _ = TEST_DB_CONNECTION.set(Mutex::new(connection));
+    Ok(())
+}
+
 pub async fn get_pool() -> SqlitePool {
     let pool = SQL_POOL
         .get_or_init(|| async { init_db().await.expect("Failed to initialize database") })
@@ -187,6 +321,9 @@ pub enum BTError {
 
     #[error("Parsing error: {0}")]
     ParsingError(#[from] parsers::ParsingError),
+
+    #[error("{0}")]
+    InvalidFilter(#[from] rss::filter::InvalidFilterError),
 }
 
 pub type BTResult<T> = Result<T, BTError>;
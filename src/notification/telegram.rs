@@ -1,8 +1,8 @@
+use crate::http;
 use crate::notification::Notifier;
 use async_trait::async_trait;
 use log::error;
-use teloxide_core::prelude::{Request, Requester};
-use teloxide_core::Bot;
+use serde::Deserialize;
 
 pub(crate) struct Telegram {
     token: String,
@@ -36,17 +36,44 @@ impl Telegram {
 #[async_trait]
 impl Notifier for Telegram {
     async fn send(&self, msg: &str) {
-        let bot = Bot::new(self.token.clone());
+        let client = http::client();
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
+        let request = client.post(&url).json(&serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": msg,
+        }));
 
-        match bot.send_message(self.chat_id.clone(), msg).send().await {
-            Ok(_) => {}
+        let response = match client.send(request).await {
+            Ok(response) => response,
             Err(err) => {
-                error!("[notification] Failed to send message: {}", err);
+                // The error message contains the url with the bot token.
+                error!("[notification] Failed to send message: {}", err.without_url());
+                return;
+            }
+        };
+        match response.json::<TelegramResponse>().await {
+            Ok(TelegramResponse { ok: true, .. }) => {}
+            Ok(TelegramResponse { description, .. }) => {
+                error!(
+                    "[notification] Failed to send message: {}",
+                    description.unwrap_or_default()
+                );
+            }
+            Err(err) => {
+                error!("[notification] Failed to send message: {}", err.without_url());
             }
         }
     }
 }
 
+/// The common response of the Telegram Bot API.
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    #[serde(default)]
+    description: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
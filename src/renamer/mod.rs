@@ -1,8 +1,14 @@
 use log::{debug, info};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use typed_builder::TypedBuilder;
 
+use crate::rss::media_info::MediaInfo;
+
 #[derive(Default, TypedBuilder, Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BangumiInfo {
     pub show_name: String,
@@ -14,14 +20,73 @@ pub struct BangumiInfo {
     pub episode: u64,
     #[builder(default)]
     pub category: Option<String>,
+    /// The episodes of a batch release, `episode` is the first one
+    #[builder(default)]
+    pub batch: Option<EpisodeRange>,
+    /// The label of a special episode like `SP2` or `13.5`, the season is 0
+    #[builder(default)]
+    pub special: Option<String>,
+    /// The parsed media info of the release, `None` for the tasks added before it's stored
+    #[serde(default)]
+    #[builder(default)]
+    pub media_info: Option<MediaInfo>,
+}
+
+/// The episodes of a batch release like `[01-12]`, the last one is unknown for
+/// releases like `合集` without a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeRange {
+    pub first: u64,
+    pub last: Option<u64>,
+}
+
+impl EpisodeRange {
+    pub fn contains(&self, episode: u64) -> bool {
+        episode >= self.first && self.last.is_none_or(|last| episode <= last)
+    }
+}
+
+/// Formatted like `1-12`, or `1-` if the last episode is unknown.
+impl Display for EpisodeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.last {
+            Some(last) => write!(f, "{}-{}", self.first, last),
+            None => write!(f, "{}-", self.first),
+        }
+    }
+}
+
+impl FromStr for EpisodeRange {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, last) = s.split_once('-').unwrap_or((s, ""));
+        Ok(Self {
+            first: first.trim().parse()?,
+            last: match last.trim() {
+                "" => None,
+                last => Some(last.parse()?),
+            },
+        })
+    }
 }
 
 impl BangumiInfo {
+    /// The show name to find the same episode in the releases of different fansubs,
+    /// see [`normalize_show_name`].
+    pub fn normalized_show_name(&self) -> String {
+        normalize_show_name(&self.show_name)
+    }
+
     pub fn folder_name(&self) -> String {
         String::from(format!("{}", self.show_name))
     }
 
+    /// The season folder, or `Specials` for season 0 as Jellyfin and Plex expect.
     pub fn sub_folder_name(&self) -> String {
+        if self.season == 0 {
+            return String::from("Specials");
+        }
         String::from(format!("Season {}", self.season))
     }
 
@@ -39,7 +104,18 @@ impl BangumiInfo {
         let mut file_name = String::new();
 
         file_name.push_str(&self.show_name.clone());
-        file_name.push_str(&format!(" S{:02}E{:02}", self.season, self.episode));
+        match self.batch {
+            Some(EpisodeRange {
+                first,
+                last: Some(last),
+            }) => file_name.push_str(&format!(" S{:02}E{:02}-E{:02}", self.season, first, last)),
+            Some(_) => file_name.push_str(&format!(" S{:02} Batch", self.season)),
+            None => file_name.push_str(&format!(" S{:02}E{:02}", self.season, self.episode)),
+        }
+
+        if let Some(ref special) = self.special {
+            file_name.push_str(&format!(" {}", special));
+        }
 
         if let Some(ref episode_name) = self.episode_name {
             if !episode_name.is_empty() {
@@ -64,13 +140,34 @@ impl BangumiInfo {
     }
 }
 
+/// Ignore the case, spaces and punctuations of a show name, e.g. `Sousou no Frieren`
+/// and `SOUSOU NO FRIEREN!` are the same show.
+pub fn normalize_show_name(show_name: &str) -> String {
+    show_name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Link the file to the correct location.
 /// e.g., if the `src_path` is `/download/Sousou no Frieren S01E01.mkv`,
 /// and the `dst_folder` is `/media/TV`,
 /// it should be linked to `/media/TV/Sousou no Frieren/Season 1/Sousou no Frieren S01E01.mkv`.
 ///
 /// Note: `src_path` means the original path may be a file or a folder.
-pub fn rename(info: &BangumiInfo, src_path: &Path, dst_folder: &Path) -> anyhow::Result<()> {
+///
+/// The files of a batch release are linked to the episodes detected from their names,
+/// see [`detect_episode`], and the files of other episodes are skipped.
+///
+/// The existing files are kept unless `replace`, e.g. a revised release replaces the
+/// files of the earlier one.
+pub fn rename(
+    info: &BangumiInfo,
+    src_path: &Path,
+    dst_folder: &Path,
+    replace: bool,
+) -> anyhow::Result<()> {
     debug!("[rename] Renaming {} to {}", src_path.display(), info.gen_path("mkv").display());
 
     if !src_path.exists() {
@@ -82,9 +179,13 @@ pub fn rename(info: &BangumiInfo, src_path: &Path, dst_folder: &Path) -> anyhow:
             .extension()
             .ok_or(anyhow::Error::msg(format!("File {} has no extension", &src_path.display())))?;
 
+        let Some(info) = file_episode_info(info, src_path) else {
+            info!("[rename] Skip file {} out of the batch", src_path.display());
+            return Ok(());
+        };
         let dst_path = dst_folder.join(info.gen_path(extension.to_str().unwrap()));
 
-        link(src_path, &dst_path)?;
+        link(src_path, &dst_path, replace)?;
     } else if src_path.is_dir() {
         for entry in std::fs::read_dir(src_path)? {
             let entry = entry?;
@@ -99,16 +200,68 @@ pub fn rename(info: &BangumiInfo, src_path: &Path, dst_folder: &Path) -> anyhow:
                 &entry_path.display()
             )))?;
 
+            let Some(info) = file_episode_info(info, &entry_path) else {
+                info!("[rename] Skip file {} out of the batch", entry_path.display());
+                continue;
+            };
             let dst_path = dst_folder.join(info.gen_path(extension.to_str().unwrap()));
 
-            link(&entry_path, &dst_path)?;
+            link(&entry_path, &dst_path, replace)?;
         }
     }
 
     Ok(())
 }
 
-pub fn link(src_path: &Path, dst_path: &Path) -> anyhow::Result<()> {
+/// The info of the episode the file belongs to. A file of a batch release belongs to
+/// the episode detected from its name, `None` if it's not an episode of the batch.
+fn file_episode_info(info: &BangumiInfo, path: &Path) -> Option<BangumiInfo> {
+    let Some(batch) = info.batch else {
+        return Some(info.clone());
+    };
+
+    let file_name = path.file_stem()?.to_str()?;
+    let episode = detect_episode(file_name).filter(|&episode| batch.contains(episode))?;
+    Some(BangumiInfo {
+        episode,
+        batch: None,
+        ..info.clone()
+    })
+}
+
+static EPISODE_RES: Lazy<[Regex; 5]> = Lazy::new(|| {
+    [
+        // Yuru Camp S03E01
+        Regex::new(r"(?i)\bS\d{1,2}E(\d{1,4})\b").unwrap(),
+        // [LoliHouse] Dungeon Meshi - 01v2 [WebRip 1080p]
+        Regex::new(r"\s-\s(\d{1,4})(?:v\d+)?(?:\s|$|\[|\()").unwrap(),
+        // [VCB-Studio] Yuru Camp [01][Ma10p_1080p]
+        Regex::new(r"[\[【](\d{1,4})(?:v\d+)?[\]】]").unwrap(),
+        // 迷宫饭 第01话
+        Regex::new(r"第(\d{1,4})[话話集]").unwrap(),
+        // Yuru Camp EP01, or 01
+        Regex::new(r"(?i)(?:\bEP?\s?|^)(\d{1,4})(?:v\d+)?(?:\b|$)").unwrap(),
+    ]
+});
+
+/// Whether the number is more likely a year than an episode, e.g. `[2024]`.
+fn is_year(number: &str) -> bool {
+    number.len() == 4 && (number.starts_with("19") || number.starts_with("20"))
+}
+
+/// Detect the episode from the name of a file in a batch release, e.g. `1` of
+/// `[LoliHouse] Dungeon Meshi - 01 [WebRip 1080p].mkv`. The years are skipped.
+pub fn detect_episode(file_name: &str) -> Option<u64> {
+    EPISODE_RES.iter().find_map(|re| {
+        re.captures_iter(file_name)
+            .map(|captures| captures.get(1).unwrap().as_str())
+            .find(|number| !is_year(number))?
+            .parse()
+            .ok()
+    })
+}
+
+pub fn link(src_path: &Path, dst_path: &Path, replace: bool) -> anyhow::Result<()> {
     info!("[rename] Linking {} to {}", src_path.display(), dst_path.display());
     if !src_path.is_file() {
         return Err(anyhow::Error::msg("Only file type can be linked"));
@@ -121,8 +274,12 @@ pub fn link(src_path: &Path, dst_path: &Path) -> anyhow::Result<()> {
     }
 
     if dst_path.exists() {
-        info!("[rename] File {} already linked", dst_path.display());
-        return Ok(());
+        if !replace {
+            info!("[rename] File {} already linked", dst_path.display());
+            return Ok(());
+        }
+        info!("[rename] Replacing file {}", dst_path.display());
+        std::fs::remove_file(dst_path)?;
     }
 
     std::fs::hard_link(src_path, dst_path)?;
@@ -151,9 +308,17 @@ pub fn replace_path(path: PathBuf, replace_rule: &str) -> PathBuf {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_show_name() {
+        assert_eq!(normalize_show_name("Sousou no Frieren"), "sousounofrieren");
+        assert_eq!(normalize_show_name("SOUSOU NO FRIEREN!"), "sousounofrieren");
+        assert_eq!(normalize_show_name("葬送的芙莉莲 "), "葬送的芙莉莲");
+        assert_ne!(normalize_show_name("Yuru Camp 2"), normalize_show_name("Yuru Camp"));
+    }
+
     #[test]
     fn test_gen_path() {
-        let bangumi_infos = vec![
+        let mut bangumi_infos = vec![
             BangumiInfo {
                 show_name: String::from("Sousou no Frieren"),
                 season: 1,
@@ -167,12 +332,35 @@ mod tests {
                 season: 1,
                 episode: 1,
                 category: None,
+                batch: None,
+                special: None,
+                media_info: None,
+            },
+            BangumiInfo {
+                show_name: String::from("Sousou no Frieren"),
+                season: 1,
+                episode: 1,
+                batch: Some(EpisodeRange {
+                    first: 1,
+                    last: Some(28),
+                }),
+                ..Default::default()
             },
         ];
 
+        bangumi_infos.push(BangumiInfo {
+            show_name: String::from("Sousou no Frieren"),
+            season: 0,
+            episode: 2,
+            special: Some(String::from("SP2")),
+            ..Default::default()
+        });
+
         let res_paths = vec![
             PathBuf::from("Sousou no Frieren/Season 1/Sousou no Frieren S01E12.mkv"),
             PathBuf::from("Sousou no Frieren/Season 1/Sousou no Frieren S01E01 冒险的终点.mkv"),
+            PathBuf::from("Sousou no Frieren/Season 1/Sousou no Frieren S01E01-E28.mkv"),
+            PathBuf::from("Sousou no Frieren/Specials/Sousou no Frieren S00E02 SP2.mkv"),
         ];
 
         for (info, res_path) in bangumi_infos.iter().zip(res_paths.iter()) {
@@ -194,13 +382,38 @@ mod tests {
             ..Default::default()
         };
 
-        rename(&bangumi_info, src_path, dst_folder).unwrap();
+        rename(&bangumi_info, src_path, dst_folder, false).unwrap();
 
         let dst_path = dst_folder.join("Sousou no Frieren/Season 1/Sousou no Frieren S01E12.mkv");
         let content = std::fs::read_to_string(dst_path).unwrap();
         assert_eq!(content, "test");
     }
 
+    #[test]
+    fn test_rename_replace() {
+        let src_path = Path::new("/tmp/Sousou no Frieren S01E13.mkv");
+        let revised_path = Path::new("/tmp/Sousou no Frieren S01E13v2.mkv");
+        std::fs::write(src_path, "v1").unwrap();
+        std::fs::write(revised_path, "v2").unwrap();
+
+        let dst_folder = Path::new("/tmp/TV");
+        let bangumi_info = BangumiInfo {
+            show_name: String::from("Sousou no Frieren"),
+            season: 1,
+            episode: 13,
+            ..Default::default()
+        };
+        let dst_path = dst_folder.join("Sousou no Frieren/Season 1/Sousou no Frieren S01E13.mkv");
+        _ = std::fs::remove_file(&dst_path);
+
+        rename(&bangumi_info, src_path, dst_folder, false).unwrap();
+        rename(&bangumi_info, revised_path, dst_folder, false).unwrap();
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "v1");
+
+        rename(&bangumi_info, revised_path, dst_folder, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "v2");
+    }
+
     #[test]
     fn test_rename_dir() {
         let src_dir = Path::new("/tmp/迷宫饭");
@@ -219,7 +432,7 @@ mod tests {
             ..Default::default()
         };
 
-        rename(&bangumi_info, src_dir, dst_folder).unwrap();
+        rename(&bangumi_info, src_dir, dst_folder, false).unwrap();
 
         assert_eq!(
             std::fs::read_to_string(dst_folder.join("迷宫饭/Season 1/迷宫饭 S01E01.mkv")).unwrap(),
@@ -230,4 +443,87 @@ mod tests {
             "ass_content"
         );
     }
+
+    #[test]
+    fn test_episode_range() {
+        let range: EpisodeRange = "1-12".parse().unwrap();
+        assert_eq!(
+            range,
+            EpisodeRange {
+                first: 1,
+                last: Some(12)
+            }
+        );
+        assert_eq!(range.to_string(), "1-12");
+        assert!(range.contains(12));
+        assert!(!range.contains(13));
+
+        let range: EpisodeRange = "1-".parse().unwrap();
+        assert_eq!(range.last, None);
+        assert_eq!(range.to_string(), "1-");
+        assert!(range.contains(100));
+        assert!("a-12".parse::<EpisodeRange>().is_err());
+    }
+
+    #[test]
+    fn test_detect_episode() {
+        let cases = [
+            ("[LoliHouse] Dungeon Meshi - 01 [WebRip 1080p HEVC-10bit AAC]", Some(1)),
+            ("[LoliHouse] Dungeon Meshi - 19v2 [WebRip 1080p HEVC-10bit AAC]", Some(19)),
+            ("[VCB-Studio] Yuru Camp [03][Ma10p_1080p][x265_flac]", Some(3)),
+            ("[VCB-Studio] Yuru Camp [03][Ma10p_1080p][x265_flac].sc", Some(3)),
+            ("Sousou no Frieren S01E28", Some(28)),
+            ("迷宫饭 第12话", Some(12)),
+            ("Yuru Camp EP07", Some(7)),
+            ("08", Some(8)),
+            ("[VCB-Studio] Yuru Camp [2018][05][Ma10p_1080p][x265_flac]", Some(5)),
+            ("[Nekomoe kissaten] Oshi no Ko [2023] - 03 [WebRip 1080p]", Some(3)),
+            ("[VCB-Studio] Yuru Camp [2018][Ma10p_1080p][x265_flac]", None),
+            ("[VCB-Studio] Yuru Camp [NCOP][Ma10p_1080p][x265_flac]", None),
+            ("[LoliHouse] Dungeon Meshi [WebRip 1080p HEVC-10bit AAC]", None),
+        ];
+
+        for (file_name, episode) in cases {
+            assert_eq!(detect_episode(file_name), episode, "file name: {}", file_name);
+        }
+    }
+
+    #[test]
+    fn test_rename_batch() {
+        let src_dir = Path::new("/tmp/Yuru Camp Batch");
+        if !src_dir.exists() {
+            std::fs::create_dir(src_dir).unwrap();
+        }
+        for name in [
+            "[01].mkv",
+            "[02].mkv",
+            "[02].sc.ass",
+            "[NCOP].mkv",
+            "[13].mkv",
+        ] {
+            let path = src_dir.join(format!("[VCB-Studio] Yuru Camp {}", name));
+            std::fs::write(path, name).unwrap();
+        }
+
+        let dst_folder = Path::new("/tmp/TV");
+        let bangumi_info = BangumiInfo {
+            show_name: String::from("Yuru Camp"),
+            season: 1,
+            episode: 1,
+            batch: Some(EpisodeRange {
+                first: 1,
+                last: Some(12),
+            }),
+            ..Default::default()
+        };
+
+        rename(&bangumi_info, src_dir, dst_folder, true).unwrap();
+
+        let season_folder = dst_folder.join("Yuru Camp/Season 1");
+        let read = |name: &str| std::fs::read_to_string(season_folder.join(name)).ok();
+        assert_eq!(read("Yuru Camp S01E01.mkv").as_deref(), Some("[01].mkv"));
+        assert_eq!(read("Yuru Camp S01E02.ass").as_deref(), Some("[02].sc.ass"));
+        assert_eq!(read("Yuru Camp S01E13.mkv"), None);
+        assert_eq!(read("Yuru Camp S01E01-E12.mkv"), None);
+    }
 }
@@ -0,0 +1,863 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local};
+use log::error;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::downloader::{self, DownloadTask};
+use crate::renamer::normalize_show_name;
+use crate::rss::filter::{RssFilter, RssFilterChain};
+use crate::rss::ledger::{RssItemDecision, RssItemRecord};
+use crate::rss::parsers;
+use crate::rss::score::ScoreConfig;
+use crate::rss::{store, DuplicatePolicy, Rss, RssSubscriptionItem, UnparsedItem};
+use crate::BTResult;
+
+/// Why a rss item is accepted or rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionReason {
+    /// Sent to the downloader
+    Accepted,
+    /// Downloaded or filtered in a previous poll
+    AlreadyProcessed,
+    /// Rejected by a global filter
+    GlobalFiltered,
+    /// Rejected by a filter of the rss
+    Filtered,
+    /// The fansub of the item is blocked
+    Blocked,
+    /// Held in the wait window of the rss for a better release of the episode
+    Waiting,
+    /// A better release of the episode is selected
+    NotSelected,
+    /// A release of the episode is already downloaded
+    Duplicate,
+    /// A higher revision of the release is in the feed
+    Superseded,
+    /// A download task of the torrent already exists
+    TaskExists,
+    /// The title of the item is not recognized
+    ParseFailed,
+    /// The downloader failed to add the torrent
+    DownloadFailed,
+}
+
+impl DecisionReason {
+    /// The decision kept in the item ledger, `None` if the ledger shouldn't change.
+    pub fn ledger_decision(&self) -> Option<RssItemDecision> {
+        match self {
+            DecisionReason::Accepted | DecisionReason::TaskExists => {
+                Some(RssItemDecision::Downloaded)
+            }
+            DecisionReason::GlobalFiltered
+            | DecisionReason::Filtered
+            | DecisionReason::Blocked
+            | DecisionReason::Duplicate
+            | DecisionReason::Superseded => Some(RssItemDecision::Filtered),
+            // Decided again on the next poll, as the selected release may fail to download.
+            DecisionReason::Waiting | DecisionReason::NotSelected => Some(RssItemDecision::Pending),
+            DecisionReason::DownloadFailed | DecisionReason::ParseFailed => {
+                Some(RssItemDecision::Failed)
+            }
+            DecisionReason::AlreadyProcessed => None,
+        }
+    }
+}
+
+/// The record of what is decided about a rss item and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemDecision {
+    /// The parsed show title, or the raw title if the item failed to be parsed
+    pub title: String,
+    pub guid: Option<String>,
+    pub season: Option<u64>,
+    pub episode: Option<u64>,
+    pub accepted: bool,
+    pub reason: DecisionReason,
+    /// The filter rejecting the item
+    pub filter: Option<RssFilter>,
+    /// The score of an accepted release, see [`ScoreConfig`]
+    pub score: Option<i64>,
+    /// The error message, or the previous decision of an already processed item
+    pub detail: Option<String>,
+}
+
+impl ItemDecision {
+    fn new(item: &RssSubscriptionItem, reason: DecisionReason) -> Self {
+        Self {
+            title: item.title.clone(),
+            guid: Some(item.guid().to_string()),
+            season: Some(item.season),
+            episode: Some(item.episode),
+            accepted: reason == DecisionReason::Accepted,
+            reason,
+            filter: None,
+            score: None,
+            detail: None,
+        }
+    }
+
+    pub fn unparsed(item: &UnparsedItem) -> Self {
+        Self {
+            title: item.title.clone(),
+            guid: Some(item.guid.clone()).filter(|guid| !guid.is_empty()),
+            season: None,
+            episode: None,
+            accepted: false,
+            reason: DecisionReason::ParseFailed,
+            filter: None,
+            score: None,
+            detail: Some(item.error.clone()),
+        }
+    }
+
+    /// Turn an accepted decision into a rejected one after the downloader failed.
+    pub fn download_failed(self, error: String) -> Self {
+        Self {
+            accepted: false,
+            reason: DecisionReason::DownloadFailed,
+            detail: Some(error),
+            ..self
+        }
+    }
+
+    fn reject(&mut self, reason: DecisionReason, detail: String) {
+        self.accepted = false;
+        self.reason = reason;
+        self.detail = Some(detail);
+    }
+}
+
+/// Decide whether the item should be downloaded, without any side effects.
+///
+/// `global_filters` and the blocked fansubs of `score_config` are applied before the
+/// filters of the rss, unless the rss skips them. `last_decision` is the decision about
+/// the item in the ledger, if any.
+pub async fn decide(
+    rss: &Rss,
+    global_filters: &RssFilterChain,
+    score_config: &ScoreConfig,
+    item: &RssSubscriptionItem,
+    last_decision: Option<RssItemDecision>,
+) -> ItemDecision {
+    if let Some(last_decision) = last_decision.filter(|d| d.is_final()) {
+        return ItemDecision {
+            detail: Some(last_decision.to_string()),
+            ..ItemDecision::new(item, DecisionReason::AlreadyProcessed)
+        };
+    }
+
+    if !rss.skip_global_filters {
+        if let Some(filter) = global_filters.find_reject(item).await {
+            return ItemDecision {
+                filter: Some(filter.clone()),
+                ..ItemDecision::new(item, DecisionReason::GlobalFiltered)
+            };
+        }
+        if score_config.is_blocked(item) {
+            return ItemDecision {
+                detail: Some(item.fansub.clone()),
+                ..ItemDecision::new(item, DecisionReason::Blocked)
+            };
+        }
+    }
+
+    // If the torrent files mismatch the filter rules, skip downloading
+    if let Some(filters) = &rss.filters {
+        if let Some(filter) = filters.find_reject(item).await {
+            return ItemDecision {
+                filter: Some(filter.clone()),
+                ..ItemDecision::new(item, DecisionReason::Filtered)
+            };
+        }
+    }
+
+    match downloader::store::is_task_exist(&item.torrent.url).await {
+        Ok(true) => return ItemDecision::new(item, DecisionReason::TaskExists),
+        Ok(false) => {}
+        // The downloader checks it again before adding the task.
+        Err(err) => error!("[rss] Failed to check the existing task: {:?}", err),
+    }
+
+    let score = score_config.score(item);
+    // A higher revision replaces the earlier release whatever the duplicate policy.
+    if item.revision.is_some() {
+        match downloader::store::query_superseded_tasks(&item.into(), item.revision).await {
+            Ok(tasks) => {
+                if let Some(task) = tasks.last() {
+                    return ItemDecision {
+                        score: Some(score),
+                        detail: Some(format!("Supersedes task {}", task.torrent_hash)),
+                        ..ItemDecision::new(item, DecisionReason::Accepted)
+                    };
+                }
+            }
+            Err(err) => error!("[rss] Failed to query the superseded tasks: {:?}", err),
+        }
+    }
+
+    let mut detail = None;
+    // A batch release isn't a duplicate of the episodes downloaded one by one.
+    if rss.duplicate_policy != DuplicatePolicy::Always && item.batch.is_none() {
+        match downloader::store::query_episode_tasks(&item.into()).await {
+            Ok(tasks) => {
+                if let Some(task) = find_duplicate(rss.duplicate_policy, score, &tasks) {
+                    return ItemDecision {
+                        score: Some(score),
+                        detail: Some(format!("Downloaded task {}", task.torrent_hash)),
+                        ..ItemDecision::new(item, DecisionReason::Duplicate)
+                    };
+                }
+                if let Some(task) = tasks.last() {
+                    detail = Some(format!("Better than task {}", task.torrent_hash));
+                }
+            }
+            Err(err) => error!("[rss] Failed to query the tasks of the episode: {:?}", err),
+        }
+    }
+
+    ItemDecision {
+        score: Some(score),
+        detail,
+        ..ItemDecision::new(item, DecisionReason::Accepted)
+    }
+}
+
+/// Find the task of the episode making the release a duplicate under the policy.
+///
+/// A task downloaded before the releases are scored is never worse.
+fn find_duplicate(
+    policy: DuplicatePolicy,
+    score: i64,
+    tasks: &[DownloadTask],
+) -> Option<&DownloadTask> {
+    match policy {
+        DuplicatePolicy::Skip => tasks.first(),
+        DuplicatePolicy::PreferHigherScore => tasks
+            .iter()
+            .find(|task| task.score.is_none_or(|task_score| task_score >= score)),
+        DuplicatePolicy::Always => None,
+    }
+}
+
+/// Decide about the items of a poll, then select the best release of each episode.
+///
+/// `last_decisions` are the decisions about the items in the ledger, in the order of `items`.
+pub async fn decide_all(
+    rss: &Rss,
+    global_filters: &RssFilterChain,
+    score_config: &ScoreConfig,
+    items: &[RssSubscriptionItem],
+    last_decisions: &[Option<RssItemDecision>],
+) -> Vec<ItemDecision> {
+    let mut decisions = Vec::new();
+    for (item, last_decision) in items.iter().zip(last_decisions) {
+        decisions.push(decide(rss, global_filters, score_config, item, *last_decision).await);
+    }
+    supersede(items, &mut decisions);
+
+    if rss.wait_minutes.is_some() {
+        let ledger = match rss.id {
+            Some(rss_id) => store::query_rss_items(rss_id).await.unwrap_or_else(|err| {
+                error!("[rss] Failed to query the items: {:?}", err);
+                vec![]
+            }),
+            None => vec![],
+        };
+        select(rss, items, &mut decisions, &ledger, Local::now());
+    }
+
+    decisions
+}
+
+/// Reject the accepted releases with a higher revision of the same release accepted,
+/// e.g. `19` is rejected if `19v2` from the same fansub is in the feed too.
+fn supersede(items: &[RssSubscriptionItem], decisions: &mut [ItemDecision]) {
+    let accepted: Vec<_> = (0..items.len())
+        .filter(|&index| decisions[index].accepted)
+        .collect();
+
+    for &index in &accepted {
+        let item = &items[index];
+        let latest = accepted
+            .iter()
+            .map(|&other| &items[other])
+            .filter(|other| {
+                other.season == item.season
+                    && other.episode == item.episode
+                    && other.fansub == item.fansub
+                    && other.media_info == item.media_info
+                    && other.special == item.special
+                    && other.batch == item.batch
+            })
+            .max_by_key(|other| other.revision.unwrap_or(1))
+            .expect("The item should be the same release of itself.");
+        if latest.revision.unwrap_or(1) > item.revision.unwrap_or(1) {
+            let detail = format!("Superseded by {}", latest.guid());
+            decisions[index].reject(DecisionReason::Superseded, detail);
+        }
+    }
+}
+
+/// Hold the accepted releases of each episode in the wait window of the rss, counted
+/// from the first one seen in the ledger, then keep the best scored one.
+///
+/// The releases coming after are decided by the duplicate policy of the rss.
+fn select(
+    rss: &Rss,
+    items: &[RssSubscriptionItem],
+    decisions: &mut [ItemDecision],
+    ledger: &[RssItemRecord],
+    now: DateTime<Local>,
+) {
+    let Some(wait_minutes) = rss.wait_minutes else {
+        return;
+    };
+
+    // The episodes of different shows in a feed like `我的番组` are apart.
+    let mut episodes: BTreeMap<(String, u64, u64, Option<&str>), Vec<usize>> = BTreeMap::new();
+    for (index, decision) in decisions.iter().enumerate() {
+        // A batch release is downloaded besides the releases of its first episode.
+        if decision.accepted && items[index].batch.is_none() {
+            let item = &items[index];
+            let show_name = normalize_show_name(&item.title);
+            episodes
+                .entry((show_name, item.season, item.episode, item.special.as_deref()))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    for ((show_name, season, episode, _), candidates) in episodes {
+        let records: Vec<_> = ledger
+            .iter()
+            .filter(|record| {
+                record.season == season
+                    && record.episode == episode
+                    && normalize_show_name(&record.title) == show_name
+            })
+            .collect();
+
+        let first_seen = candidates
+            .iter()
+            .filter_map(|&index| {
+                let guid = items[index].guid();
+                records.iter().find(|record| record.guid == guid)
+            })
+            .map(|record| record.first_seen)
+            .min()
+            .unwrap_or(now);
+        let until = first_seen + Duration::minutes(wait_minutes as i64);
+        if now < until {
+            for index in candidates {
+                let detail = format!("Until {}", until.format("%Y-%m-%d %H:%M"));
+                decisions[index].reject(DecisionReason::Waiting, detail);
+            }
+            continue;
+        }
+
+        // The higher revision, then the first one in the feed wins a tie.
+        let best = *candidates
+            .iter()
+            .max_by_key(|&&index| (decisions[index].score, items[index].revision, Reverse(index)))
+            .expect("An episode should have candidates.");
+        for index in candidates.into_iter().filter(|&index| index != best) {
+            let detail = format!(
+                "Selected {} scoring {}",
+                items[best].guid(),
+                decisions[best].score.unwrap_or_default()
+            );
+            decisions[index].reject(DecisionReason::NotSelected, detail);
+        }
+    }
+}
+
+/// Fetch the rss and explain what would be decided about each item,
+/// without downloading anything.
+pub async fn explain(rss: &Rss) -> BTResult<Vec<ItemDecision>> {
+    let feeds = parsers::parse(rss).await?;
+    let global_filters = store::query_global_filters().await?.into();
+    let score_config = store::get_score_config().await?;
+
+    let mut last_decisions = Vec::new();
+    for item in &feeds.items {
+        let last_decision = match rss.id {
+            Some(rss_id) => store::get_item_decision(rss_id, item.guid())
+                .await
+                .unwrap_or_else(|err| {
+                    error!("[rss] Failed to query the item decision: {:?}", err);
+                    None
+                }),
+            None => None,
+        };
+        last_decisions.push(last_decision);
+    }
+
+    let mut decisions =
+        decide_all(rss, &global_filters, &score_config, &feeds.items, &last_decisions).await;
+    decisions.extend(feeds.unparsed.iter().map(ItemDecision::unparsed));
+
+    Ok(decisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::{TaskStatus, TorrentMeta};
+    use crate::renamer::{BangumiInfo, EpisodeRange};
+    use crate::rss::filter::FilterRegex;
+    use crate::rss::RssType;
+    use crate::test::gen_torrent_with_custom_filename;
+
+    async fn gen_rss_item(episode: u64, filename: &str) -> RssSubscriptionItem {
+        let url = format!("https://example.com/decision/{}.torrent", episode);
+        let torrent = gen_torrent_with_custom_filename(filename);
+        downloader::update_torrent_cache(&url, &torrent).await;
+
+        RssSubscriptionItem {
+            url: format!("https://example.com/decision/{}", episode),
+            title: "Yuru Camp".to_string(),
+            season: 3,
+            episode,
+            torrent: TorrentMeta::builder().url(url).build(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decide() {
+        crate::init().await;
+
+        let filter = RssFilter::FilenameRegex(FilterRegex::new(r"\.mp4$").unwrap());
+        let rss = Rss::builder()
+            .url("https://example.com/decision".to_string())
+            .rss_type(RssType::Generic)
+            .filters(Some(RssFilterChain(vec![filter.clone()])))
+            .build();
+        let no_global_filters = RssFilterChain(vec![]);
+
+        let item = gen_rss_item(1, "[Up to 21°C] Yuru Camp△ Season 3 - 01.mkv").await;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert!(decision.accepted);
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        assert_eq!(decision.guid.as_deref(), Some("https://example.com/decision/1"));
+        assert_eq!(decision.episode, Some(1));
+
+        let decision = decide(
+            &rss,
+            &no_global_filters,
+            &ScoreConfig::default(),
+            &item,
+            Some(RssItemDecision::Failed),
+        )
+        .await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+
+        let decision = decide(
+            &rss,
+            &no_global_filters,
+            &ScoreConfig::default(),
+            &item,
+            Some(RssItemDecision::Downloaded),
+        )
+        .await;
+        assert!(!decision.accepted);
+        assert_eq!(decision.reason, DecisionReason::AlreadyProcessed);
+        assert_eq!(decision.detail.as_deref(), Some("downloaded"));
+
+        let item = gen_rss_item(2, "[Up to 21°C] Yuru Camp△ Season 3 - 02.mp4").await;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert!(!decision.accepted);
+        assert_eq!(decision.reason, DecisionReason::Filtered);
+        assert_eq!(decision.filter, Some(filter.clone()));
+
+        let decision = decision.download_failed("unreachable".to_string());
+        assert_eq!(decision.reason, DecisionReason::DownloadFailed);
+        assert_eq!(decision.reason.ledger_decision(), Some(RssItemDecision::Failed));
+
+        let global_filter = RssFilter::FilenameRegex(FilterRegex::new("Baha").unwrap());
+        let global_filters = RssFilterChain(vec![global_filter.clone()]);
+        let item = gen_rss_item(3, "[Up to 21°C] Yuru Camp△ Season 3 - 03 (Baha).mp4").await;
+        let decision = decide(&rss, &global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::GlobalFiltered);
+        assert_eq!(decision.filter, Some(global_filter));
+        assert_eq!(decision.reason.ledger_decision(), Some(RssItemDecision::Filtered));
+
+        // The rss skipping the global filters still applies its own.
+        let rss = Rss {
+            skip_global_filters: true,
+            ..rss
+        };
+        let decision = decide(&rss, &global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Filtered);
+        assert_eq!(decision.filter, Some(filter));
+
+        // The blocked fansubs are skipped with the global filters.
+        let score_config = ScoreConfig {
+            blocked_fansubs: vec!["up to 21°c".to_string()],
+            ..Default::default()
+        };
+        let item = RssSubscriptionItem {
+            fansub: "[Up to 21°C]".to_string(),
+            ..gen_rss_item(1, "[Up to 21°C] Yuru Camp△ Season 3 - 01.mkv").await
+        };
+        let decision = decide(&rss, &no_global_filters, &score_config, &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        let rss = Rss {
+            skip_global_filters: false,
+            ..rss
+        };
+        let decision = decide(&rss, &no_global_filters, &score_config, &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Blocked);
+        assert_eq!(decision.detail.as_deref(), Some("[Up to 21°C]"));
+    }
+
+    #[tokio::test]
+    async fn test_decide_duplicate() {
+        crate::init().await;
+
+        // The episode is downloaded from another rss with a different show name.
+        let task = DownloadTask::builder()
+            .rss_id(Some(-1))
+            .torrent_hash("4b5e5ab1b38d4bdc5aa42c0b09e0fa46ec6f4b89".to_string())
+            .torrent_url(Some("https://example.com/duplicate/fallback.torrent".to_string()))
+            .start_time(Local::now())
+            .status(TaskStatus::Downloading)
+            .renamed(false)
+            .score(Some(1))
+            .build();
+        let info = BangumiInfo::builder()
+            .show_name("YURU CAMP!".to_string())
+            .season(3)
+            .episode(5)
+            .build();
+        downloader::store::add_task(task.rss_id, &task, &info)
+            .await
+            .unwrap();
+
+        let mut rss = Rss::builder()
+            .url("https://example.com/duplicate".to_string())
+            .rss_type(RssType::Generic)
+            .build();
+        let no_global_filters = RssFilterChain(vec![]);
+        let item = gen_rss_item(5, "[Up to 21°C] Yuru Camp△ Season 3 - 05.mkv").await;
+
+        // Every release is downloaded unless the rss opts in to skip the duplicates.
+        assert_eq!(rss.duplicate_policy, DuplicatePolicy::Always);
+        rss.duplicate_policy = DuplicatePolicy::Skip;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Duplicate);
+        assert_eq!(decision.detail, Some(format!("Downloaded task {}", task.torrent_hash)));
+
+        // The item scores higher with the default resolutions.
+        let item = RssSubscriptionItem {
+            media_info: "[1080p]".to_string(),
+            ..item
+        };
+        rss.duplicate_policy = DuplicatePolicy::PreferHigherScore;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        assert_eq!(decision.detail, Some(format!("Better than task {}", task.torrent_hash)));
+
+        rss.duplicate_policy = DuplicatePolicy::Always;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        assert_eq!(decision.detail, None);
+    }
+
+    #[tokio::test]
+    async fn test_decide_duplicate_batch() {
+        crate::init().await;
+
+        // The batch is stored as its first episode.
+        let task = DownloadTask::builder()
+            .rss_id(Some(-1))
+            .torrent_hash("0c1e3a5b7d9f1b3d5f7a9c1e3b5d7f9a1c3e5b70".to_string())
+            .torrent_url(Some("https://example.com/batch/09-12.torrent".to_string()))
+            .start_time(Local::now())
+            .status(TaskStatus::Completed)
+            .renamed(true)
+            .build();
+        let info = BangumiInfo::builder()
+            .show_name("Yuru Camp".to_string())
+            .season(3)
+            .episode(9)
+            .batch(Some("9-12".parse().unwrap()))
+            .build();
+        downloader::store::add_task(task.rss_id, &task, &info)
+            .await
+            .unwrap();
+
+        let rss = Rss::builder()
+            .url("https://example.com/batch".to_string())
+            .rss_type(RssType::Generic)
+            .duplicate_policy(DuplicatePolicy::Skip)
+            .build();
+        let no_global_filters = RssFilterChain(vec![]);
+
+        // Neither the first episode of the batch nor the others are duplicates of it.
+        let item = gen_rss_item(9, "[Up to 21°C] Yuru Camp△ Season 3 - 09.mkv").await;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        let item = gen_rss_item(11, "[Up to 21°C] Yuru Camp△ Season 3 - 11.mkv").await;
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_decide_revision() {
+        crate::init().await;
+
+        let task = DownloadTask::builder()
+            .rss_id(Some(-1))
+            .torrent_hash("9d2a4c6f1e0b3a5d7c9e1f3a5b7d9f1a3c5e7b90".to_string())
+            .torrent_url(Some("https://example.com/revision/7.torrent".to_string()))
+            .start_time(Local::now())
+            .status(TaskStatus::Completed)
+            .renamed(true)
+            .build();
+        let info = BangumiInfo::builder()
+            .show_name("Yuru Camp".to_string())
+            .display_name(Some("[LoliHouse][1080p]".to_string()))
+            .season(3)
+            .episode(7)
+            .build();
+        downloader::store::add_task(task.rss_id, &task, &info)
+            .await
+            .unwrap();
+
+        let rss = Rss::builder()
+            .url("https://example.com/revision".to_string())
+            .rss_type(RssType::Generic)
+            .duplicate_policy(DuplicatePolicy::Skip)
+            .build();
+        let no_global_filters = RssFilterChain(vec![]);
+        let item = RssSubscriptionItem {
+            fansub: "[LoliHouse]".to_string(),
+            media_info: "[1080p]".to_string(),
+            ..gen_rss_item(7, "[LoliHouse] Yuru Camp S3 - 07v2 [1080p].mkv").await
+        };
+
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Duplicate);
+
+        let item = RssSubscriptionItem {
+            revision: Some(2),
+            ..item
+        };
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Accepted);
+        assert_eq!(decision.detail, Some(format!("Supersedes task {}", task.torrent_hash)));
+
+        // The revision of another fansub doesn't supersede the task.
+        let item = RssSubscriptionItem {
+            fansub: "[SweetSub]".to_string(),
+            ..item
+        };
+        let decision = decide(&rss, &no_global_filters, &ScoreConfig::default(), &item, None).await;
+        assert_eq!(decision.reason, DecisionReason::Duplicate);
+    }
+
+    #[test]
+    fn test_supersede() {
+        let gen_item = |guid: &str, fansub: &str, revision| RssSubscriptionItem {
+            url: guid.to_string(),
+            title: "Yuru Camp".to_string(),
+            season: 3,
+            episode: 8,
+            revision,
+            fansub: fansub.to_string(),
+            ..Default::default()
+        };
+        let items = [
+            gen_item("a", "[LoliHouse]", None),
+            gen_item("b", "[LoliHouse]", Some(2)),
+            gen_item("c", "[SweetSub]", None),
+        ];
+        let mut decisions: Vec<_> = items
+            .iter()
+            .map(|item| ItemDecision::new(item, DecisionReason::Accepted))
+            .collect();
+
+        supersede(&items, &mut decisions);
+        let reasons: Vec<_> = decisions.iter().map(|decision| decision.reason).collect();
+        assert_eq!(
+            reasons,
+            [
+                DecisionReason::Superseded,
+                DecisionReason::Accepted,
+                DecisionReason::Accepted
+            ]
+        );
+        assert_eq!(decisions[0].detail.as_deref(), Some("Superseded by b"));
+        assert_eq!(decisions[0].reason.ledger_decision(), Some(RssItemDecision::Filtered));
+    }
+
+    #[test]
+    fn test_find_duplicate() {
+        let gen_task = |score| {
+            DownloadTask::builder()
+                .rss_id(None)
+                .torrent_hash("".to_string())
+                .torrent_url(None)
+                .start_time(Local::now())
+                .status(TaskStatus::Completed)
+                .renamed(true)
+                .score(score)
+                .build()
+        };
+
+        let tasks = [gen_task(Some(10)), gen_task(Some(30))];
+        assert!(find_duplicate(DuplicatePolicy::Skip, 20, &tasks).is_some());
+        assert!(find_duplicate(DuplicatePolicy::Skip, 20, &[]).is_none());
+        let duplicate = find_duplicate(DuplicatePolicy::PreferHigherScore, 20, &tasks);
+        assert_eq!(duplicate.and_then(|task| task.score), Some(30));
+        assert!(find_duplicate(DuplicatePolicy::PreferHigherScore, 40, &tasks).is_none());
+        assert!(find_duplicate(DuplicatePolicy::PreferHigherScore, 40, &[gen_task(None)]).is_some());
+        assert!(find_duplicate(DuplicatePolicy::Always, 0, &tasks).is_none());
+    }
+
+    #[test]
+    fn test_select() {
+        let now = Local::now();
+        let mut rss = Rss::builder()
+            .url("https://example.com/select".to_string())
+            .rss_type(RssType::Generic)
+            .wait_minutes(Some(120))
+            .build();
+        let gen_item = |guid: &str, episode| RssSubscriptionItem {
+            url: guid.to_string(),
+            title: "Yuru Camp".to_string(),
+            season: 3,
+            episode,
+            ..Default::default()
+        };
+        let batch = RssSubscriptionItem {
+            batch: Some(EpisodeRange {
+                first: 1,
+                last: Some(12),
+            }),
+            ..gen_item("d", 1)
+        };
+        let items = [gen_item("a", 1), gen_item("b", 1), gen_item("c", 2), batch];
+        let gen_decisions = || -> Vec<_> {
+            items
+                .iter()
+                .zip([10, 20, 10, 30])
+                .map(|(item, score)| ItemDecision {
+                    score: Some(score),
+                    ..ItemDecision::new(item, DecisionReason::Accepted)
+                })
+                .collect()
+        };
+        let gen_record = |guid: &str, episode, minutes_ago, decision| RssItemRecord {
+            id: 0,
+            rss_id: 1,
+            guid: guid.to_string(),
+            title: "Yuru Camp".to_string(),
+            season: 3,
+            episode,
+            torrent_url: "".to_string(),
+            first_seen: now - Duration::minutes(minutes_ago),
+            last_seen: now,
+            decision,
+            reason: None,
+            filter: None,
+            detail: None,
+        };
+        let reasons = |decisions: &[ItemDecision]| -> Vec<_> {
+            decisions.iter().map(|decision| decision.reason).collect()
+        };
+
+        let mut ledger = vec![
+            gen_record("a", 1, 60, RssItemDecision::Pending),
+            gen_record("b", 1, 10, RssItemDecision::Pending),
+            gen_record("c", 2, 0, RssItemDecision::Pending),
+        ];
+        let mut decisions = gen_decisions();
+        select(&rss, &items, &mut decisions, &ledger, now);
+        assert_eq!(
+            reasons(&decisions),
+            [
+                DecisionReason::Waiting,
+                DecisionReason::Waiting,
+                DecisionReason::Waiting,
+                DecisionReason::Accepted
+            ]
+        );
+        assert!(!decisions[0].accepted);
+        assert_eq!(decisions[0].reason.ledger_decision(), Some(RssItemDecision::Pending));
+
+        // The window starts from the first release of the episode.
+        ledger[0].first_seen = now - Duration::minutes(150);
+        let mut decisions = gen_decisions();
+        select(&rss, &items, &mut decisions, &ledger, now);
+        assert_eq!(
+            reasons(&decisions),
+            [
+                DecisionReason::NotSelected,
+                DecisionReason::Accepted,
+                DecisionReason::Waiting,
+                DecisionReason::Accepted
+            ]
+        );
+        assert!(decisions[1].accepted);
+        assert_eq!(decisions[0].detail.as_deref(), Some("Selected b scoring 20"));
+
+        // The same episode of another show is selected apart.
+        let other_show = RssSubscriptionItem {
+            title: "Oshi no Ko".to_string(),
+            ..gen_item("e", 1)
+        };
+        let show_items = [gen_item("a", 1), gen_item("b", 1), other_show];
+        let mut show_decisions: Vec<_> = show_items
+            .iter()
+            .zip([10, 20, 10])
+            .map(|(item, score)| ItemDecision {
+                score: Some(score),
+                ..ItemDecision::new(item, DecisionReason::Accepted)
+            })
+            .collect();
+        let mut show_ledger = ledger[..2].to_vec();
+        show_ledger.push(RssItemRecord {
+            title: "Oshi no Ko".to_string(),
+            ..gen_record("e", 1, 150, RssItemDecision::Pending)
+        });
+        select(&rss, &show_items, &mut show_decisions, &show_ledger, now);
+        assert_eq!(
+            reasons(&show_decisions),
+            [
+                DecisionReason::NotSelected,
+                DecisionReason::Accepted,
+                DecisionReason::Accepted
+            ]
+        );
+
+        // Every accepted release is downloaded without a wait window.
+        rss.wait_minutes = None;
+        let mut decisions = gen_decisions();
+        select(&rss, &items, &mut decisions, &ledger, now);
+        assert_eq!(reasons(&decisions), [DecisionReason::Accepted; 4]);
+    }
+
+    #[test]
+    fn test_unparsed_decision() {
+        let decision = ItemDecision::unparsed(&UnparsedItem {
+            title: "[LoliHouse] 招募翻译校对".to_string(),
+            guid: "https://share.dmhy.org/topics/view/1.html".to_string(),
+            error: "Unrecognized episode".to_string(),
+        });
+        assert!(!decision.accepted);
+        assert_eq!(decision.guid.as_deref(), Some("https://share.dmhy.org/topics/view/1.html"));
+        assert_eq!(decision.reason, DecisionReason::ParseFailed);
+        assert_eq!(decision.reason.ledger_decision(), Some(RssItemDecision::Failed));
+        assert_eq!(
+            serde_json::to_value(&decision).unwrap()["reason"],
+            serde_json::json!("parse_failed")
+        );
+    }
+}
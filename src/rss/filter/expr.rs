@@ -0,0 +1,982 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use log::error;
+
+use super::{regex_error_reason, FilterRegex};
+use crate::downloader::TorrentFile;
+use crate::rss::media_info::MediaInfo;
+use crate::rss::parsers::parse_size;
+use crate::rss::RssSubscriptionItem;
+
+/// The error of parsing a filter expression.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message} at column {}", .position + 1)]
+pub struct FilterParseError {
+    /// The position in characters where the error occurs, starting from 0
+    pub position: usize,
+    pub message: String,
+}
+
+impl FilterParseError {
+    pub(crate) fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+/// A boolean expression over the fields of a rss item, e.g.
+/// `name ~ "1080p" and not (name ~ "CR|Crunchyroll") and episode >= 5 and size < 2GiB`.
+///
+/// ## Fields
+/// - `name`: the name of the torrent, which is only the folder name of a batch torrent
+/// - `any_file`, `all_files`: the paths of the files in the torrent, starting with the
+///   folder name, e.g. `any_file ~ "\.mp4$"` matches if one of the files is a mp4 and
+///   `all_files !~ "CR"` matches if none of the files contains CR
+/// - `title`, `fansub`, `media_info`: parsed from the title of the item
+/// - `source`, `video_codec`, `platform`, `resolution`, `bit_depth`: parsed from the media
+///   info, e.g. `source == "WebRip" and resolution >= 1080p`
+/// - `audio`, `subtitles`: the audio codecs, and the subtitle languages with the kind,
+///   separated by spaces, e.g. `subtitles ~ "CHS" and subtitles !~ "Hardsub"`
+/// - `episode`, `season`
+/// - `category`: the category of the rss
+/// - `feed_category`: the category provided by the feed, e.g. `Anime - English-translated`
+///   of nyaa or `5070,127720` of a torznab indexer
+/// - `trusted`: `yes` or `no` if the feed tells whether the uploader is trusted, e.g.
+///   `trusted == "yes"`
+/// - `size`: the size provided by the feed or the torrent, e.g. `700MB` or `2GiB`
+/// - `files`: the number of files in the torrent
+/// - `pub_date`: the publication date, e.g. `2024-03-01` or `2024-03-01T12:00:00+08:00`
+/// - `age`: the time since the publication, e.g. `30m`, `12h`, `7d` or `2w`
+///
+/// ## Operators
+/// - Text fields: `~` and `!~` match a case insensitive regex, `==` and `!=` compare exactly
+/// - Other fields: `==`, `!=`, `<`, `<=`, `>`, `>=`
+/// - Number fields: `in` matches an inclusive range, e.g. `episode in 1..12`
+/// - `and`, `or`, `not` and parentheses, `and` binds tighter than `or`
+///
+/// A comparison on a missing value, e.g. the size of an item from a feed without sizes or
+/// the name of a torrent that failed to be fetched, is unknown rather than false, so
+/// `not` and `!=` don't turn it into a match. `false and unknown` is false and
+/// `true or unknown` is true, and the expression only matches if the result is true.
+#[derive(Debug, Clone)]
+pub struct FilterExpr {
+    source: String,
+    root: Node,
+}
+
+impl FilterExpr {
+    pub fn parse(source: &str) -> Result<Self, FilterParseError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: source.chars().count(),
+        };
+        let root = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(FilterParseError::new(
+                token.position,
+                format!("Unexpected {} after the expression", token.kind),
+            ));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Evaluate the expression against the item, fetching the torrent only if needed.
+    pub async fn eval(&self, rss_item: &RssSubscriptionItem) -> bool {
+        let needs_name = self
+            .root
+            .any(&|node| matches!(node, Node::Text(TextField::Name, ..)));
+        let name = if needs_name {
+            match rss_item.torrent.get_name().await {
+                Ok(name) => Some(name),
+                Err(err) => {
+                    error!("[filter] Failed to get torrent name: {:?}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let needs_files = self.root.any(&|node| match node {
+            Node::Text(field, ..) => matches!(field, TextField::AnyFile | TextField::AllFiles),
+            Node::Number(field, ..) | Node::Range(field, ..) => {
+                *field == NumberField::Files
+                    || (*field == NumberField::Size && rss_item.size.is_none())
+            }
+            _ => false,
+        });
+        let files = if needs_files {
+            match rss_item.torrent.get_files().await {
+                Ok(files) => Some(files),
+                Err(err) => {
+                    error!("[filter] Failed to get torrent files: {:?}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        self.root
+            .eval(&EvalContext {
+                rss_item,
+                media_info: rss_item.parsed_media_info(),
+                name,
+                files,
+                now: Local::now(),
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl PartialEq for FilterExpr {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for FilterExpr {}
+
+impl Display for FilterExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = FilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextField {
+    Name,
+    Title,
+    Fansub,
+    MediaInfo,
+    Source,
+    VideoCodec,
+    Audio,
+    Subtitles,
+    Platform,
+    Category,
+    FeedCategory,
+    Trusted,
+    /// Matches if any file path in the torrent matches
+    AnyFile,
+    /// Matches if every file path in the torrent matches
+    AllFiles,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberField {
+    Episode,
+    Season,
+    /// The height, e.g. `1080`
+    Resolution,
+    BitDepth,
+    Size,
+    Files,
+    /// In seconds
+    Age,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Text(TextField),
+    Number(NumberField),
+    PubDate,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        let field = match name {
+            "name" => Field::Text(TextField::Name),
+            "title" => Field::Text(TextField::Title),
+            "fansub" => Field::Text(TextField::Fansub),
+            "media_info" => Field::Text(TextField::MediaInfo),
+            "source" => Field::Text(TextField::Source),
+            "video_codec" => Field::Text(TextField::VideoCodec),
+            "audio" => Field::Text(TextField::Audio),
+            "subtitles" => Field::Text(TextField::Subtitles),
+            "platform" => Field::Text(TextField::Platform),
+            "category" => Field::Text(TextField::Category),
+            "feed_category" => Field::Text(TextField::FeedCategory),
+            "trusted" => Field::Text(TextField::Trusted),
+            "any_file" => Field::Text(TextField::AnyFile),
+            "all_files" => Field::Text(TextField::AllFiles),
+            "episode" => Field::Number(NumberField::Episode),
+            "season" => Field::Number(NumberField::Season),
+            "resolution" => Field::Number(NumberField::Resolution),
+            "bit_depth" => Field::Number(NumberField::BitDepth),
+            "size" => Field::Number(NumberField::Size),
+            "files" => Field::Number(NumberField::Files),
+            "age" => Field::Number(NumberField::Age),
+            "pub_date" => Field::PubDate,
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TextOp {
+    Match(FilterRegex),
+    NotMatch(FilterRegex),
+    Eq(String),
+    Ne(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn compare<T: Ord>(&self, lhs: T, rhs: T) -> bool {
+        match self {
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Ge => lhs >= rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Text(TextField, TextOp),
+    Number(NumberField, CompareOp, u64),
+    /// An inclusive range
+    Range(NumberField, u64, u64),
+    PubDate(CompareOp, DateTime<Local>),
+}
+
+struct EvalContext<'a> {
+    rss_item: &'a RssSubscriptionItem,
+    media_info: MediaInfo,
+    /// The torrent name, `None` if it's not needed or failed to be fetched
+    name: Option<String>,
+    /// The torrent files, `None` if they're not needed or failed to be fetched
+    files: Option<Vec<TorrentFile>>,
+    now: DateTime<Local>,
+}
+
+impl EvalContext<'_> {
+    fn text(&self, field: TextField) -> Option<Cow<'_, str>> {
+        let media_info = &self.media_info;
+        match field {
+            TextField::Name => self.name.as_deref().map(Cow::Borrowed),
+            TextField::Title => Some(Cow::Borrowed(&self.rss_item.title)),
+            TextField::Fansub => Some(Cow::Borrowed(&self.rss_item.fansub)),
+            TextField::MediaInfo => Some(Cow::Borrowed(&self.rss_item.media_info)),
+            TextField::Source => media_info.source.map(|source| source.to_string().into()),
+            TextField::VideoCodec => media_info.video_codec.map(|codec| codec.to_string().into()),
+            TextField::Audio => Some(join(&media_info.audio_codecs).into()),
+            TextField::Subtitles => {
+                let mut subtitles = join(&media_info.subtitles);
+                if let Some(kind) = media_info.subtitle_kind {
+                    subtitles = format!("{} {}", subtitles, kind).trim_start().to_string();
+                }
+                Some(subtitles.into())
+            }
+            TextField::Platform => media_info
+                .platform
+                .map(|platform| platform.to_string().into()),
+            TextField::Category => Some(Cow::Borrowed(&self.rss_item.category)),
+            TextField::FeedCategory => Some(self.rss_item.feed_category.as_str())
+                .filter(|category| !category.is_empty())
+                .map(Cow::Borrowed),
+            TextField::Trusted => self
+                .rss_item
+                .trusted
+                .map(|trusted| Cow::Borrowed(if trusted { "yes" } else { "no" })),
+            TextField::AnyFile | TextField::AllFiles => None,
+        }
+    }
+
+    fn number(&self, field: NumberField) -> Option<u64> {
+        match field {
+            NumberField::Episode => Some(self.rss_item.episode),
+            NumberField::Season => Some(self.rss_item.season),
+            NumberField::Resolution => self.media_info.resolution,
+            NumberField::BitDepth => self.media_info.bit_depth,
+            NumberField::Size => self.rss_item.size.or_else(|| {
+                let files = self.files.as_ref()?;
+                Some(files.iter().map(|file| file.length).sum())
+            }),
+            NumberField::Files => self.files.as_ref().map(|files| files.len() as u64),
+            NumberField::Age => self
+                .rss_item
+                .pub_date
+                .map(|pub_date| (self.now - pub_date).num_seconds().max(0) as u64),
+        }
+    }
+}
+
+impl Node {
+    /// Whether any node in the tree satisfies the predicate.
+    fn any(&self, predicate: &dyn Fn(&Node) -> bool) -> bool {
+        if predicate(self) {
+            return true;
+        }
+        match self {
+            Node::And(lhs, rhs) | Node::Or(lhs, rhs) => lhs.any(predicate) || rhs.any(predicate),
+            Node::Not(node) => node.any(predicate),
+            _ => false,
+        }
+    }
+
+    /// Evaluate the node, `None` if the result is unknown because of a missing value.
+    fn eval(&self, ctx: &EvalContext) -> Option<bool> {
+        match self {
+            Node::And(lhs, rhs) => match (lhs.eval(ctx), rhs.eval(ctx)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Node::Or(lhs, rhs) => match (lhs.eval(ctx), rhs.eval(ctx)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Node::Not(node) => node.eval(ctx).map(|value| !value),
+            Node::Text(field, op) => {
+                let is_match = |text: &str| match op {
+                    TextOp::Match(regex) => regex.is_match(text),
+                    TextOp::NotMatch(regex) => !regex.is_match(text),
+                    TextOp::Eq(value) => text == value,
+                    TextOp::Ne(value) => text != value,
+                };
+                let files = ctx.files.as_deref().filter(|files| !files.is_empty());
+                match field {
+                    TextField::AnyFile => {
+                        files.map(|files| files.iter().any(|file| is_match(&file.path)))
+                    }
+                    TextField::AllFiles => {
+                        files.map(|files| files.iter().all(|file| is_match(&file.path)))
+                    }
+                    _ => ctx.text(*field).map(|text| is_match(&text)),
+                }
+            }
+            Node::Number(field, op, value) => {
+                ctx.number(*field).map(|number| op.compare(number, *value))
+            }
+            Node::Range(field, start, end) => ctx
+                .number(*field)
+                .map(|number| (*start..=*end).contains(&number)),
+            Node::PubDate(op, value) => ctx
+                .rss_item
+                .pub_date
+                .map(|pub_date| op.compare(pub_date, *value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    /// A word starting with a digit, e.g. `5`, `2GiB`, `1..12` or `2024-03-01`
+    Number(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Ident(ident) => write!(f, "'{}'", ident),
+            TokenKind::Str(s) => write!(f, "string {:?}", s),
+            TokenKind::Number(number) => write!(f, "number '{}'", number),
+            TokenKind::Op(op) => write!(f, "'{}'", op),
+            TokenKind::LParen => f.write_str("'('"),
+            TokenKind::RParen => f.write_str("')'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+const OPERATORS: [&str; 8] = ["!~", "==", "!=", "<=", ">=", "~", "<", ">"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, FilterParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i;
+        let kind = if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else if c == '(' {
+            i += 1;
+            TokenKind::LParen
+        } else if c == ')' {
+            i += 1;
+            TokenKind::RParen
+        } else if c == '"' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(FilterParseError::new(position, "Unterminated string")),
+                    Some('"') => break,
+                    Some('\\') => {
+                        match chars.get(i + 1) {
+                            Some(c @ ('"' | '\\')) => value.push(*c),
+                            // Keep other escapes for the regex, e.g. `\.mkv$`
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(*c);
+                            }
+                            None => {
+                                return Err(FilterParseError::new(position, "Unterminated string"))
+                            }
+                        }
+                        i += 2;
+                    }
+                    Some(c) => {
+                        value.push(*c);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            TokenKind::Str(value)
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || ".-:+".contains(chars[i]))
+            {
+                i += 1;
+            }
+            TokenKind::Number(chars[start..i].iter().collect())
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            TokenKind::Ident(chars[start..i].iter().collect())
+        } else if let Some(op) = OPERATORS.iter().find(|op| {
+            op.chars()
+                .enumerate()
+                .all(|(n, c)| chars.get(i + n) == Some(&c))
+        }) {
+            i += op.len();
+            TokenKind::Op(op)
+        } else {
+            return Err(FilterParseError::new(position, format!("Unexpected character '{}'", c)));
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// The position of the end of the source
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, FilterParseError> {
+        match self.tokens.get(self.index) {
+            Some(token) => {
+                self.index += 1;
+                Ok(token.clone())
+            }
+            None => Err(FilterParseError::new(
+                self.end,
+                format!("Expected {}, found the end of the expression", expected),
+            )),
+        }
+    }
+
+    fn next_is_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Ident(ident),
+                ..
+            }) if ident == keyword => {
+                self.index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Node, FilterParseError> {
+        let mut node = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            node = Node::Or(Box::new(node), Box::new(self.parse_and()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, FilterParseError> {
+        let mut node = self.parse_unary()?;
+        while self.next_is_keyword("and") {
+            node = Node::And(Box::new(node), Box::new(self.parse_unary()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, FilterParseError> {
+        if self.next_is_keyword("not") {
+            return Ok(Node::Not(Box::new(self.parse_unary()?)));
+        }
+
+        let token = self.next("a condition")?;
+        match token.kind {
+            TokenKind::LParen => {
+                let node = self.parse_or()?;
+                let token = self.next("')'")?;
+                if token.kind != TokenKind::RParen {
+                    return Err(FilterParseError::new(
+                        token.position,
+                        format!("Expected ')', found {}", token.kind),
+                    ));
+                }
+                Ok(node)
+            }
+            TokenKind::Ident(field) => self.parse_condition(&field, token.position),
+            kind => Err(FilterParseError::new(
+                token.position,
+                format!("Expected a field name, found {}", kind),
+            )),
+        }
+    }
+
+    fn parse_condition(&mut self, name: &str, position: usize) -> Result<Node, FilterParseError> {
+        let field = Field::from_name(name)
+            .ok_or_else(|| FilterParseError::new(position, format!("Unknown field '{}'", name)))?;
+
+        let op = self.next("an operator")?;
+        let op_str = match &op.kind {
+            TokenKind::Op(op_str) => *op_str,
+            TokenKind::Ident(ident) if ident == "in" => "in",
+            kind => {
+                return Err(FilterParseError::new(
+                    op.position,
+                    format!("Expected an operator after '{}', found {}", name, kind),
+                ))
+            }
+        };
+        let unsupported_op = || {
+            FilterParseError::new(
+                op.position,
+                format!("Operator '{}' can't be used with the field '{}'", op_str, name),
+            )
+        };
+
+        if let Field::Text(field) = field {
+            if !["~", "!~", "==", "!="].contains(&op_str) {
+                return Err(unsupported_op());
+            }
+            let value = self.next("a string")?;
+            let TokenKind::Str(value_str) = value.kind else {
+                return Err(FilterParseError::new(
+                    value.position,
+                    format!("Expected a string, found {}", value.kind),
+                ));
+            };
+            let regex = || {
+                FilterRegex::new(&value_str)
+                    .map_err(|err| FilterParseError::new(value.position, regex_error_reason(&err)))
+            };
+            let op = match op_str {
+                "~" => TextOp::Match(regex()?),
+                "!~" => TextOp::NotMatch(regex()?),
+                "==" => TextOp::Eq(value_str),
+                _ => TextOp::Ne(value_str),
+            };
+            return Ok(Node::Text(field, op));
+        }
+
+        let supported = match op_str {
+            "~" | "!~" => false,
+            "in" => matches!(field, Field::Number(_)),
+            _ => true,
+        };
+        if !supported {
+            return Err(unsupported_op());
+        }
+
+        let value = self.next("a value")?;
+        let TokenKind::Number(word) = value.kind else {
+            return Err(FilterParseError::new(
+                value.position,
+                format!("Expected a value for the field '{}', found {}", name, value.kind),
+            ));
+        };
+        let parse_error = |message: String| FilterParseError::new(value.position, message);
+
+        match (field, op_str) {
+            (Field::Number(field), "in") => {
+                let (start, end) = word.split_once("..").ok_or_else(|| {
+                    parse_error(format!("Expected a range like '1..12', found '{}'", word))
+                })?;
+                let start = parse_number(field, name, start).map_err(parse_error)?;
+                let end = parse_number(field, name, end).map_err(parse_error)?;
+                if start > end {
+                    return Err(parse_error(format!("Empty range '{}'", word)));
+                }
+                Ok(Node::Range(field, start, end))
+            }
+            (Field::Number(field), _) => {
+                let number = parse_number(field, name, &word).map_err(parse_error)?;
+                Ok(Node::Number(field, compare_op(op_str), number))
+            }
+            (_, _) => {
+                let date = parse_date(&word)
+                    .ok_or_else(|| parse_error(format!("Invalid date '{}'", word)))?;
+                Ok(Node::PubDate(compare_op(op_str), date))
+            }
+        }
+    }
+}
+
+/// The comparison operators, excluding the text only ones.
+fn compare_op(op: &str) -> CompareOp {
+    match op {
+        "==" => CompareOp::Eq,
+        "!=" => CompareOp::Ne,
+        "<" => CompareOp::Lt,
+        "<=" => CompareOp::Le,
+        ">" => CompareOp::Gt,
+        ">=" => CompareOp::Ge,
+        _ => unreachable!("not a comparison operator: {}", op),
+    }
+}
+
+fn parse_number(field: NumberField, name: &str, word: &str) -> Result<u64, String> {
+    match field {
+        NumberField::Size => parse_size(word).ok_or_else(|| format!("Invalid size '{}'", word)),
+        NumberField::Age => {
+            parse_duration(word).ok_or_else(|| format!("Invalid duration '{}'", word))
+        }
+        // 1080p
+        NumberField::Resolution => word
+            .trim_end_matches(['p', 'P'])
+            .parse()
+            .map_err(|_| format!("Invalid resolution '{}'", word)),
+        _ => word
+            .parse()
+            .map_err(|_| format!("Expected a number for the field '{}', found '{}'", name, word)),
+    }
+}
+
+/// Parse a duration like `30m`, `12h` or `7d` into seconds, a bare number is in seconds.
+fn parse_duration(word: &str) -> Option<u64> {
+    let unit_start = word
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(word.len());
+    let value: u64 = word[..unit_start].parse().ok()?;
+    let multiplier = match &word[unit_start..] {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    value.checked_mul(multiplier)
+}
+
+/// The values separated by spaces, e.g. `AAC FLAC`.
+fn join<T: Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse a date like `2024-03-01`, `2024-03-01T12:00:00` in local time,
+/// or `2024-03-01T12:00:00+08:00`.
+fn parse_date(word: &str) -> Option<DateTime<Local>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(word) {
+        return Some(date.with_timezone(&Local));
+    }
+    let date = match NaiveDateTime::parse_from_str(word, "%Y-%m-%dT%H:%M:%S") {
+        Ok(date) => date,
+        Err(_) => NaiveDate::parse_from_str(word, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?,
+    };
+    date.and_local_timezone(Local).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::{update_torrent_cache, Torrent, TorrentMeta};
+    use crate::test::gen_torrent_with_files;
+    use chrono::Duration;
+
+    fn gen_rss_item(episode: u64, size: Option<u64>) -> RssSubscriptionItem {
+        RssSubscriptionItem {
+            title: "Sousou no Frieren".to_string(),
+            fansub: "[SubsPlease]".to_string(),
+            media_info: "(1080p) [C3C9B8A4]".to_string(),
+            season: 1,
+            episode,
+            size,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eval() {
+        let expr =
+            FilterExpr::parse(r#"title ~ "frieren" and episode >= 5 and size < 2GiB"#).unwrap();
+        assert!(expr.eval(&gen_rss_item(5, Some(1453921124))).await);
+        assert!(!expr.eval(&gen_rss_item(4, Some(1453921124))).await);
+        assert!(!expr.eval(&gen_rss_item(5, Some(3 << 30))).await);
+
+        let expr = FilterExpr::parse(r#"not (size > 1.5GB) or episode == 1"#).unwrap();
+        assert!(expr.eval(&gen_rss_item(1, Some(3 << 30))).await);
+        assert!(!expr.eval(&gen_rss_item(2, Some(1_500_000_001))).await);
+
+        // `and` binds tighter than `or`.
+        let expr = FilterExpr::parse(r#"episode == 1 or episode == 2 and season == 2"#).unwrap();
+        assert!(expr.eval(&gen_rss_item(1, None)).await);
+        assert!(!expr.eval(&gen_rss_item(2, None)).await);
+
+        let expr =
+            FilterExpr::parse(r#"title == "Sousou no Frieren" and title !~ "\.mp4$""#).unwrap();
+        assert!(expr.eval(&gen_rss_item(1, None)).await);
+    }
+
+    #[tokio::test]
+    async fn test_eval_item_fields() {
+        let expr = FilterExpr::parse(
+            r#"fansub == "[SubsPlease]" and media_info ~ "1080p" and episode in 13..28"#,
+        )
+        .unwrap();
+        assert!(expr.eval(&gen_rss_item(13, None)).await);
+        assert!(expr.eval(&gen_rss_item(28, None)).await);
+        assert!(!expr.eval(&gen_rss_item(12, None)).await);
+
+        let mut rss_item = gen_rss_item(1, None);
+        rss_item.media_info = "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]".to_string();
+        let expr = FilterExpr::parse(
+            r#"source == "WebRip" and resolution >= 1080p and video_codec ~ "hevc|avc"
+                and bit_depth == 10 and audio == "AAC" and subtitles ~ "chs.*embedded""#,
+        )
+        .unwrap();
+        assert!(expr.eval(&rss_item).await);
+        let expr = FilterExpr::parse(r#"resolution > 1080 or platform == "CR""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+        // The item without a platform doesn't match the negative comparison either.
+        let expr = FilterExpr::parse(r#"platform != "CR""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+
+        let expr = FilterExpr::parse(r#"feed_category ~ "english""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+        rss_item.feed_category = "Anime - English-translated".to_string();
+        assert!(expr.eval(&rss_item).await);
+
+        let expr = FilterExpr::parse(r#"trusted == "yes""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+        rss_item.trusted = Some(true);
+        assert!(expr.eval(&rss_item).await);
+
+        let expr = FilterExpr::parse("age < 7d").unwrap();
+        // Missing values never match.
+        assert!(!expr.eval(&rss_item).await);
+        rss_item.pub_date = Some(Local::now() - Duration::days(3));
+        assert!(expr.eval(&rss_item).await);
+        rss_item.pub_date = Some(Local::now() - Duration::days(8));
+        assert!(!expr.eval(&rss_item).await);
+
+        let expr = FilterExpr::parse("pub_date >= 2024-01-19T16:00:00Z").unwrap();
+        rss_item.pub_date = parse_date("2024-01-19T16:01:55+00:00");
+        assert!(expr.eval(&rss_item).await);
+        rss_item.pub_date = parse_date("2024-01-12T16:08:12+00:00");
+        assert!(!expr.eval(&rss_item).await);
+    }
+
+    #[tokio::test]
+    async fn test_eval_torrent_files() {
+        let dot_torrent =
+            std::fs::read("tests/dataset/bb95e3795d653b274dbc32e1c48d2d3543417156.torrent")
+                .unwrap();
+        let url = "https://example.com/expr/bb95e3795d653b274dbc32e1c48d2d3543417156.torrent";
+        update_torrent_cache(url, &Torrent::from_bytes(&dot_torrent).unwrap()).await;
+
+        let mut rss_item = gen_rss_item(22, None);
+        rss_item.torrent = TorrentMeta::builder().url(url.to_string()).build();
+
+        // The size of the files is used if the feed doesn't provide one.
+        let expr = FilterExpr::parse("files == 1 and size in 700MiB..1GiB").unwrap();
+        assert!(expr.eval(&rss_item).await);
+        rss_item.size = Some(2 << 30);
+        assert!(!expr.eval(&rss_item).await);
+
+        // The name of a batch torrent is the folder name.
+        let expr = FilterExpr::parse(r#"name ~ "1080p""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+        let expr = FilterExpr::parse(r#"any_file ~ "1080p""#).unwrap();
+        assert!(expr.eval(&rss_item).await);
+    }
+
+    #[tokio::test]
+    async fn test_eval_file_quantifiers() {
+        let torrent = gen_torrent_with_files(
+            "[Nekomoe kissaten] Sousou no Frieren [01-28]",
+            &[
+                "Sousou no Frieren - 01 [1080p].mkv",
+                "Sousou no Frieren - 02 [1080p].mkv",
+                "SPs/Sousou no Frieren - NCOP [1080p].mp4",
+            ],
+        );
+        let url = "https://example.com/expr/frieren-batch.torrent";
+        update_torrent_cache(url, &torrent).await;
+        let mut rss_item = gen_rss_item(1, None);
+        rss_item.torrent = TorrentMeta::builder().url(url.to_string()).build();
+
+        let cases = [
+            (r#"any_file ~ "\.mp4$""#, true),
+            (r#"all_files ~ "\.mkv$""#, false),
+            (r#"all_files ~ "1080p""#, true),
+            (r#"any_file !~ "\.mkv$""#, true),
+            (r#"all_files !~ "\.mp4$""#, false),
+            (r#"any_file ~ "^\[Nekomoe kissaten\] Sousou no Frieren \[01-28\]/SPs/""#, true),
+            (r#"any_file == "Sousou no Frieren - 01 [1080p].mkv""#, false),
+            (r#"files == 3 and not any_file ~ "720p""#, true),
+        ];
+        for (source, expect) in cases {
+            let expr = FilterExpr::parse(source).unwrap();
+            assert_eq!(expr.eval(&rss_item).await, expect, "{}", source);
+        }
+
+        // Nothing matches if the files are unknown, e.g. for magnet links.
+        rss_item.torrent = TorrentMeta::builder()
+            .url("magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567".to_string())
+            .build();
+        let expr = FilterExpr::parse(r#"all_files !~ "\.mp4$""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+        let expr = FilterExpr::parse(r#"not any_file ~ "\.mp4$""#).unwrap();
+        assert!(!expr.eval(&rss_item).await);
+    }
+
+    #[tokio::test]
+    async fn test_eval_unknown() {
+        // Neither the name nor the files of a magnet link without `dn` can be fetched.
+        let mut rss_item = gen_rss_item(1, None);
+        rss_item.torrent = TorrentMeta::builder()
+            .url("magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567".to_string())
+            .build();
+
+        let cases = [
+            (r#"not (name ~ "CR")"#, false),
+            (r#"name !~ "CR""#, false),
+            (r#"not (name ~ "CR" or episode == 2)"#, false),
+            (r#"not (name ~ "CR") and episode == 1"#, false),
+            (r#"not (name ~ "CR") or episode == 1"#, true),
+            (r#"not (name ~ "CR" and episode == 2)"#, true),
+            (r#"not (files > 1)"#, false),
+        ];
+        for (source, expect) in cases {
+            let expr = FilterExpr::parse(source).unwrap();
+            assert_eq!(expr.eval(&rss_item).await, expect, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_and_date() {
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("30m"), Some(1800));
+        assert_eq!(parse_duration("2w"), Some(1209600));
+        assert_eq!(parse_duration("7x"), None);
+
+        let date = parse_date("2024-03-01").unwrap();
+        assert_eq!(date.naive_local().to_string(), "2024-03-01 00:00:00");
+        assert_eq!(parse_date("2024-03-01T12:00:00+08:00"), parse_date("2024-03-01T04:00:00Z"));
+        assert!(parse_date("2024-13-01").is_none());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            (
+                r#"name ~ "1080p" and"#,
+                18,
+                "Expected a condition, found the end of the expression",
+            ),
+            (r#"name ~ 1080"#, 7, "Expected a string, found number '1080'"),
+            (r#"nam ~ "1080p""#, 0, "Unknown field 'nam'"),
+            (r#"episode ~ "5""#, 8, "Operator '~' can't be used with the field 'episode'"),
+            (
+                r#"episode >= 5GiB"#,
+                11,
+                "Expected a number for the field 'episode', found '5GiB'",
+            ),
+            (r#"size < 2GX"#, 7, "Invalid size '2GX'"),
+            (r#"age < 7x"#, 6, "Invalid duration '7x'"),
+            (r#"pub_date > 2024-13-01"#, 11, "Invalid date '2024-13-01'"),
+            (
+                r#"pub_date in 2024-01-01..2024-02-01"#,
+                9,
+                "Operator 'in' can't be used with the field 'pub_date'",
+            ),
+            (r#"episode in 5"#, 11, "Expected a range like '1..12', found '5'"),
+            (r#"episode in 12..1"#, 11, "Empty range '12..1'"),
+            (
+                r#"season == "2""#,
+                10,
+                "Expected a value for the field 'season', found string \"2\"",
+            ),
+            (r#"(episode >= 5"#, 13, "Expected ')', found the end of the expression"),
+            (r#"episode >= 5)"#, 12, "Unexpected ')' after the expression"),
+            (r#"name ~ "1080p"#, 7, "Unterminated string"),
+            (r#"name ~ "[1080p""#, 7, "Invalid regex: unclosed character class"),
+            (r#"name ~ "1080p" && episode > 1"#, 15, "Unexpected character '&'"),
+            (r#"episode 5"#, 8, "Expected an operator after 'episode', found number '5'"),
+        ];
+        for (source, position, message) in cases {
+            let err = FilterExpr::parse(source).unwrap_err();
+            assert_eq!(err, FilterParseError::new(position, message), "{}", source);
+        }
+
+        let err = FilterExpr::parse("nam ~ \"1080p\"").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown field 'nam' at column 1");
+    }
+}
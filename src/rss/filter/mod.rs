@@ -0,0 +1,598 @@
+pub mod expr;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use log::error;
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::mikan::parse_rss_item_info;
+use crate::rss::parsers::strip_file_extension;
+use crate::rss::RssSubscriptionItem;
+use expr::{FilterExpr, FilterParseError};
+
+/// A case insensitive regex, compiled once when the filter is loaded.
+#[derive(Debug, Clone)]
+pub struct FilterRegex(Regex);
+
+impl FilterRegex {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl PartialEq for FilterRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for FilterRegex {}
+
+impl Display for FilterRegex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The reason of a regex error is in its last line, the others point at the pattern.
+fn regex_error_reason(err: &regex::Error) -> String {
+    let message = err.to_string();
+    let reason = message.lines().last().unwrap_or_default();
+    format!("Invalid regex: {}", reason.trim_start_matches("error: "))
+}
+
+/// A filter failed to be parsed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid filter {filter:?}: {error}")]
+pub struct InvalidFilterError {
+    pub filter: String,
+    pub error: FilterParseError,
+}
+
+/// RssFilter matches the file names in torrent files, then we can
+/// download the matched versions.
+///
+/// Filters are stored as strings: `FilenameRegex-<regex>`, `IncludeFilenameRegex-<regex>`,
+/// or a [`FilterExpr`] like `name ~ "1080p" and episode >= 5`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum RssFilter {
+    /// Exclude the items whose file name matches the given regex.
+    FilenameRegex(FilterRegex),
+    /// Only include the items whose file name matches the given regex.
+    IncludeFilenameRegex(FilterRegex),
+    /// Only include the items satisfying the expression.
+    Expr(FilterExpr),
+    /// A stored filter which can't be parsed any more. It rejects every item,
+    /// so a broken filter never lets a whole feed be downloaded.
+    Invalid(InvalidFilterError),
+}
+
+impl RssFilter {
+    /// Parse a filter loaded from the database, keeping it as [`RssFilter::Invalid`]
+    /// if it's invalid.
+    pub fn from_stored(content: &str) -> Self {
+        content.parse().unwrap_or_else(|err: InvalidFilterError| {
+            error!("[filter] {}", err);
+            RssFilter::Invalid(err)
+        })
+    }
+}
+
+impl Display for RssFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RssFilter::FilenameRegex(regex) => write!(f, "FilenameRegex-{}", regex),
+            RssFilter::IncludeFilenameRegex(regex) => write!(f, "IncludeFilenameRegex-{}", regex),
+            RssFilter::Expr(expr) => write!(f, "{}", expr),
+            RssFilter::Invalid(err) => write!(f, "{}", err.filter),
+        }
+    }
+}
+
+impl FromStr for RssFilter {
+    type Err = InvalidFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |error| InvalidFilterError {
+            filter: s.to_string(),
+            error,
+        };
+        let legacy = [
+            ("FilenameRegex-", RssFilter::FilenameRegex as fn(FilterRegex) -> RssFilter),
+            ("IncludeFilenameRegex-", RssFilter::IncludeFilenameRegex),
+        ];
+        for (prefix, variant) in legacy {
+            if let Some(pattern) = s.strip_prefix(prefix) {
+                return FilterRegex::new(pattern).map(variant).map_err(|err| {
+                    invalid(FilterParseError::new(prefix.len(), regex_error_reason(&err)))
+                });
+            }
+        }
+        FilterExpr::parse(s).map(RssFilter::Expr).map_err(invalid)
+    }
+}
+
+impl TryFrom<String> for RssFilter {
+    type Error = InvalidFilterError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RssFilter> for String {
+    fn from(filter: RssFilter) -> Self {
+        filter.to_string()
+    }
+}
+
+// NOTE: Don't use rss content to match file name, because rss content and rss parsers are not reliable.
+async fn match_by_torrent_info_name<F>(rss_item: &RssSubscriptionItem, match_fn: F) -> bool
+where
+    F: FnOnce(&str) -> bool,
+{
+    match rss_item.torrent.get_name().await {
+        Ok(name) => match_fn(&name),
+        Err(err) => {
+            error!("[filter] Failed to get torrent name: {:?}", err);
+            false
+        }
+    }
+}
+
+impl RssFilter {
+    /// Whether the item must match the filter to be downloaded.
+    pub fn is_include(&self) -> bool {
+        match self {
+            RssFilter::FilenameRegex(_) => false,
+            RssFilter::IncludeFilenameRegex(_) | RssFilter::Expr(_) | RssFilter::Invalid(_) => true,
+        }
+    }
+
+    pub async fn is_match(&self, rss_item: &RssSubscriptionItem) -> bool {
+        match self {
+            RssFilter::FilenameRegex(regex) | RssFilter::IncludeFilenameRegex(regex) => {
+                match_by_torrent_info_name(rss_item, |name| regex.is_match(name)).await
+            }
+            RssFilter::Expr(expr) => expr.eval(rss_item).await,
+            RssFilter::Invalid(err) => {
+                error!("[filter] Rejected by {}", err);
+                false
+            }
+        }
+    }
+
+    /// Whether the filter keeps the item from being downloaded.
+    pub async fn is_reject(&self, rss_item: &RssSubscriptionItem) -> bool {
+        self.is_match(rss_item).await != self.is_include()
+    }
+}
+
+/// RssFilterChain is a chain of filters.
+///
+/// An item is accepted only if it matches every include filter and none of the
+/// exclude filters, so the order of the filters doesn't matter. A chain without
+/// include filters accepts everything not excluded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RssFilterChain(pub Vec<RssFilter>);
+
+impl RssFilterChain {
+    /// Parse the filters saved in the database, see [`RssFilter::from_stored`].
+    pub fn from_stored(content: &str) -> Self {
+        match serde_json::from_str::<Vec<String>>(content) {
+            Ok(filters) => Self(filters.iter().map(|f| RssFilter::from_stored(f)).collect()),
+            Err(err) => {
+                let err = InvalidFilterError {
+                    filter: content.to_string(),
+                    error: FilterParseError::new(0, format!("Not a list of filters: {}", err)),
+                };
+                error!("[filter] {}", err);
+                Self(vec![RssFilter::Invalid(err)])
+            }
+        }
+    }
+
+    /// Return the error of the first invalid filter, if any.
+    pub fn validate(&self) -> Result<(), InvalidFilterError> {
+        match self.0.iter().find_map(|filter| match filter {
+            RssFilter::Invalid(err) => Some(err),
+            _ => None,
+        }) {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the given RSS item is accepted by all filters.
+    pub async fn is_match(&self, rss_item: &RssSubscriptionItem) -> bool {
+        self.find_reject(rss_item).await.is_none()
+    }
+
+    /// Find the first filter rejecting the given RSS item.
+    pub async fn find_reject(&self, rss_item: &RssSubscriptionItem) -> Option<&RssFilter> {
+        for filter in &self.0 {
+            if filter.is_reject(rss_item).await {
+                return Some(filter);
+            }
+        }
+
+        None
+    }
+
+    /// Test the chain against the items, and tell the filter rejecting each one.
+    pub async fn test(&self, items: &[RssSubscriptionItem]) -> Vec<FilterTestResult> {
+        let mut results = Vec::new();
+        for item in items {
+            let filter = self.find_reject(item).await.cloned();
+            let name = match item.torrent.get_name().await {
+                Ok(name) => name,
+                Err(_) => item.title.clone(),
+            };
+            results.push(FilterTestResult {
+                name,
+                accepted: filter.is_none(),
+                filter,
+            });
+        }
+        results
+    }
+}
+
+/// Whether an item passes a filter chain, see [`RssFilterChain::test`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterTestResult {
+    /// The torrent name, or the title of the item if the torrent is inaccessible
+    pub name: String,
+    pub accepted: bool,
+    /// The filter rejecting the item
+    pub filter: Option<RssFilter>,
+}
+
+/// An item named after a sample file name, to test the filters without a feed.
+///
+/// The name is parsed like a release title of the feeds, so the fields like `episode`
+/// and `fansub` are set, or only the title is set if it's not recognized.
+///
+/// The name is carried by a magnet link, so no torrent file is fetched. The fields
+/// needing the torrent files, e.g. `files` and `any_file`, never match.
+pub fn sample_item(name: &str) -> RssSubscriptionItem {
+    let magnet = Url::parse_with_params(
+        "magnet:?",
+        [
+            ("xt", format!("urn:btih:{}", "0".repeat(40))),
+            ("dn", name.to_string()),
+        ],
+    )
+    .expect("The magnet link should be valid.");
+    let info =
+        parse_rss_item_info(&strip_file_extension(name)).unwrap_or_else(|| RssSubscriptionItem {
+            title: name.to_string(),
+            ..Default::default()
+        });
+    RssSubscriptionItem {
+        torrent: TorrentMeta::builder().url(magnet.to_string()).build(),
+        ..info
+    }
+}
+
+/// A filter applied to every rss before its own filters, e.g. `FilenameRegex-\.mp4$`.
+/// An rss can opt out with `skip_global_filters`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypedBuilder)]
+pub struct GlobalFilter {
+    #[serde(default)]
+    #[builder(default)]
+    pub id: Option<i64>,
+    pub filter: RssFilter,
+    #[serde(default)]
+    #[builder(default)]
+    pub description: Option<String>,
+}
+
+impl From<Vec<GlobalFilter>> for RssFilterChain {
+    fn from(filters: Vec<GlobalFilter>) -> Self {
+        Self(filters.into_iter().map(|f| f.filter).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn match_by_regex(regex: &str, filename: &str) -> bool {
+        FilterRegex::new(regex).unwrap().is_match(filename)
+    }
+
+    #[test]
+    fn test_match_by_regex() {
+        assert!(match_by_regex(
+            "CR|Crunchyroll",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (cr 1920x1080 AVC AAC MKV) [5BE12A49].mkv"
+        ));
+        assert!(match_by_regex(
+            "CR|Crunchyroll",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (crunchyroll 1920x1080 AVC AAC MKV) [5BE12A49].mkv"
+        ));
+        assert!(!match_by_regex(
+            "CR|Crunchyroll",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (Baha 1920x1080 AVC AAC MKV) [5BE12A49].mkv"
+        ));
+
+        assert!(match_by_regex(
+            r"\.mkv$",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (CR 1920x1080 AVC AAC MKV) [5BE12A49].mkv"
+        ));
+        assert!(match_by_regex(
+            r"\.mkv$",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (CR 1920x1080 AVC AAC MKV) [5BE12A49].MKV"
+        ));
+        assert!(!match_by_regex(
+            r"\.mkv$",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (CR 1920x1080 AVC AAC MKV) [5BE12A49].mp4"
+        ));
+    }
+
+    async fn gen_rss_item_with_filename(filename: &str) -> RssSubscriptionItem {
+        use crate::downloader::update_torrent_cache;
+        use crate::test::gen_torrent_with_custom_filename;
+
+        let url = &format!("https://example.com/example.torrent?filename={}", filename);
+        let torrent = gen_torrent_with_custom_filename(filename);
+        update_torrent_cache(url, &torrent).await;
+
+        RssSubscriptionItem {
+            url: "".to_string(),
+            title: "".to_string(),
+            episode_title: "".to_string(),
+            season: 1,
+            episode: 1,
+            fansub: "".to_string(),
+            media_info: "".to_string(),
+            torrent: TorrentMeta::builder()
+                .url(url.to_string())
+                .category(None)
+                .save_path(None)
+                .build(),
+            category: "".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_chain() {
+        let filter_chain = RssFilterChain(vec![
+            RssFilter::FilenameRegex(FilterRegex::new("CR|Crunchyroll").unwrap()),
+            RssFilter::FilenameRegex(FilterRegex::new("Baha").unwrap()),
+            RssFilter::FilenameRegex(FilterRegex::new(r#"\.mp4$"#).unwrap()),
+        ]);
+
+        let filenames = vec![
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (CR 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (crunchyroll 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (Baha 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (Baha 1920x1080 AVC AAC MKV) [5BE12A49].mp4",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (friDay 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+        ];
+        let results = vec![false, false, false, false, true];
+        for (filename, result) in filenames.iter().zip(results.iter()) {
+            let rss_item = gen_rss_item_with_filename(filename).await;
+            assert_eq!(filter_chain.is_match(&rss_item).await, *result, "{}", filename);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_chain_exclude_only() {
+        let filter_chain = RssFilterChain(vec![RssFilter::FilenameRegex(
+            FilterRegex::new(r#"\.mp4$"#).unwrap(),
+        )]);
+
+        let filenames = vec![
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (CR 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (crunchyroll 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (Baha 1920x1080 AVC AAC MKV) [5BE12A49].mkv",
+            "[Up to 21°C] Yuru Camp△ Season 3 - 01 (Baha 1920x1080 AVC AAC MKV) [5BE12A49].mp4",
+        ];
+        let results = vec![true, true, true, false];
+        for (filename, result) in filenames.iter().zip(results.iter()) {
+            let rss_item = gen_rss_item_with_filename(filename).await;
+            assert_eq!(filter_chain.is_match(&rss_item).await, *result, "{}", filename);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_chain_include() {
+        // Must match 1080p and must not match CR.
+        let filter_chain = RssFilterChain(vec![
+            RssFilter::IncludeFilenameRegex(FilterRegex::new("1080p").unwrap()),
+            RssFilter::FilenameRegex(FilterRegex::new("CR|Crunchyroll").unwrap()),
+        ]);
+
+        let filenames = [
+            "[SubsPlease] Sousou no Frieren - 18 (1080p) [F02B9CEE].mkv",
+            "[SubsPlease] Sousou no Frieren - 18 (720p) [A2D7B6A1].mkv",
+            "[Erai-raws] Sousou no Frieren - 18 [1080p CR WEB-DL AVC AAC][MultiSub].mkv",
+            "[Erai-raws] Sousou no Frieren - 18 [720p CR WEB-DL AVC AAC][MultiSub].mkv",
+        ];
+        let results = [true, false, false, false];
+        for (filename, result) in filenames.iter().zip(results.iter()) {
+            let rss_item = gen_rss_item_with_filename(filename).await;
+            assert_eq!(filter_chain.is_match(&rss_item).await, *result, "{}", filename);
+        }
+
+        let rss_item = gen_rss_item_with_filename(filenames[1]).await;
+        assert_eq!(filter_chain.find_reject(&rss_item).await, Some(&filter_chain.0[0]));
+        let rss_item = gen_rss_item_with_filename(filenames[2]).await;
+        assert_eq!(filter_chain.find_reject(&rss_item).await, Some(&filter_chain.0[1]));
+
+        // Every include filter must match.
+        let filter_chain = RssFilterChain(vec![
+            RssFilter::IncludeFilenameRegex(FilterRegex::new("1080p").unwrap()),
+            RssFilter::IncludeFilenameRegex(FilterRegex::new(r"\.mkv$").unwrap()),
+        ]);
+        let rss_item = gen_rss_item_with_filename(filenames[0]).await;
+        assert!(filter_chain.is_match(&rss_item).await);
+        let rss_item =
+            gen_rss_item_with_filename("[SubsPlease] Sousou no Frieren - 18 (1080p).mp4").await;
+        assert!(!filter_chain.is_match(&rss_item).await);
+    }
+
+    #[test]
+    fn test_serialize_filter_chain() {
+        let filter_chain = RssFilterChain(vec![
+            RssFilter::IncludeFilenameRegex(FilterRegex::new("1080p").unwrap()),
+            RssFilter::FilenameRegex(FilterRegex::new("CR|Web-DL").unwrap()),
+        ]);
+        let content = serde_json::to_string(&filter_chain).unwrap();
+        assert_eq!(content, r#"["IncludeFilenameRegex-1080p","FilenameRegex-CR|Web-DL"]"#);
+
+        let filter_chain: RssFilterChain = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            filter_chain.0[0],
+            RssFilter::IncludeFilenameRegex(FilterRegex::new("1080p").unwrap())
+        );
+        // The part after the first `-` is the regex.
+        assert_eq!(
+            filter_chain.0[1],
+            RssFilter::FilenameRegex(FilterRegex::new("CR|Web-DL").unwrap())
+        );
+
+        assert!(serde_json::from_str::<RssFilterChain>(r#"["ExcludeRegex-CR"]"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_filter() {
+        let err = serde_json::from_str::<RssFilterChain>(r#"["FilenameRegex-[1080p"]"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(r#"Invalid filter "FilenameRegex-[1080p""#), "{}", err);
+        assert!(err.contains("unclosed character class"), "{}", err);
+
+        let err = "name ~ \"(CR\"".parse::<RssFilter>().unwrap_err();
+        assert_eq!(err.filter, "name ~ \"(CR\"");
+        assert_eq!(err.error.position, 7);
+
+        // Filters saved before they were validated are kept, but reject everything.
+        let filter_chain =
+            RssFilterChain::from_stored(r#"["FilenameRegex-CR", "FilenameRegex-[1080p"]"#);
+        assert!(matches!(filter_chain.0[0], RssFilter::FilenameRegex(_)));
+        assert!(matches!(filter_chain.0[1], RssFilter::Invalid(_)));
+        assert_eq!(filter_chain.0[1].to_string(), "FilenameRegex-[1080p");
+        assert!(filter_chain.validate().is_err());
+
+        let rss_item = gen_rss_item_with_filename("[SubsPlease] Yuru Camp - 01 (1080p).mkv").await;
+        assert!(!filter_chain.is_match(&rss_item).await);
+
+        let filter_chain = RssFilterChain::from_stored("FilenameRegex-CR");
+        assert!(filter_chain.validate().is_err());
+        assert!(!filter_chain.is_match(&rss_item).await);
+    }
+
+    #[tokio::test]
+    async fn test_filter_chain_test() {
+        let filter_chain = RssFilterChain(vec![
+            RssFilter::IncludeFilenameRegex(FilterRegex::new("1080p").unwrap()),
+            RssFilter::FilenameRegex(FilterRegex::new(r"CR|\.mp4$").unwrap()),
+        ]);
+        let items = [
+            sample_item("[SubsPlease] Sousou no Frieren - 18 (1080p) [F02B9CEE].mkv"),
+            sample_item("[SubsPlease] Sousou no Frieren - 18 (720p) [A2D7B6A1].mkv"),
+            sample_item("[Erai-raws] Sousou no Frieren - 18 [1080p CR WEB-DL AVC AAC].mkv"),
+        ];
+        assert_eq!(items[0].title, "Sousou no Frieren");
+        assert_eq!(items[0].episode, 18);
+        assert_eq!(sample_item("招募翻译校对").title, "招募翻译校对");
+
+        let results = filter_chain.test(&items).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].name, "[SubsPlease] Sousou no Frieren - 18 (1080p) [F02B9CEE].mkv");
+        assert!(results[0].accepted);
+        assert_eq!(results[0].filter, None);
+        assert!(!results[1].accepted);
+        assert_eq!(results[1].filter.as_ref(), Some(&filter_chain.0[0]));
+        assert!(!results[2].accepted);
+        assert_eq!(results[2].filter.as_ref(), Some(&filter_chain.0[1]));
+
+        // The structured fields are parsed from the sample names.
+        let filter_chain = RssFilterChain::from_stored(r#"["episode >= 5"]"#);
+        let items = [
+            sample_item("[SubsPlease] Sousou no Frieren - 18 (1080p) [F02B9CEE].mkv"),
+            sample_item("[SubsPlease] Sousou no Frieren - 04 (1080p) [A2D7B6A1].mkv"),
+        ];
+        let results = filter_chain.test(&items).await;
+        assert!(results[0].accepted);
+        assert!(!results[1].accepted);
+    }
+
+    #[tokio::test]
+    async fn test_filter_expr() {
+        let content =
+            r#"["name ~ \"1080p\" and not (name ~ \"CR|Crunchyroll\") and episode >= 5"]"#;
+        let filter_chain: RssFilterChain = serde_json::from_str(content).unwrap();
+        assert!(matches!(filter_chain.0[0], RssFilter::Expr(_)));
+        assert_eq!(serde_json::to_string(&filter_chain).unwrap(), content);
+
+        let filenames = [
+            "[SubsPlease] Sousou no Frieren - 18 (1080p) [F02B9CEE].mkv",
+            "[SubsPlease] Sousou no Frieren - 18 (720p) [A2D7B6A1].mkv",
+            "[Erai-raws] Sousou no Frieren - 18 [1080p CR WEB-DL AVC AAC][MultiSub].mkv",
+        ];
+        let results = [true, false, false];
+        for (filename, result) in filenames.iter().zip(results.iter()) {
+            let mut rss_item = gen_rss_item_with_filename(filename).await;
+            rss_item.episode = 18;
+            assert_eq!(filter_chain.is_match(&rss_item).await, *result, "{}", filename);
+        }
+
+        let mut rss_item = gen_rss_item_with_filename(filenames[0]).await;
+        rss_item.episode = 4;
+        assert!(!filter_chain.is_match(&rss_item).await);
+
+        // The exclusion doesn't let the item through if the name can't be fetched.
+        let filter_chain: RssFilterChain =
+            serde_json::from_str(r#"["not (name ~ \"CR|Crunchyroll\")"]"#).unwrap();
+        rss_item.torrent = TorrentMeta::builder()
+            .url("magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567".to_string())
+            .build();
+        assert!(!filter_chain.is_match(&rss_item).await);
+
+        let err =
+            serde_json::from_str::<RssFilterChain>(r#"["name ~ \"1080p\" and"]"#).unwrap_err();
+        assert!(err.to_string().contains("at column 19"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_filter_magnet() {
+        let filter_chain = RssFilterChain(vec![RssFilter::FilenameRegex(
+            FilterRegex::new(r#"\.mp4$"#).unwrap(),
+        )]);
+
+        // The name of a magnet link comes from `dn`, without downloading anything.
+        let mut rss_item = gen_rss_item_with_filename("").await;
+        rss_item.torrent = TorrentMeta::builder()
+            .url(
+                "magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567&dn=Frieren+17.mp4"
+                    .to_string(),
+            )
+            .build();
+        assert!(!filter_chain.is_match(&rss_item).await);
+
+        rss_item.torrent.url = rss_item.torrent.url.replace(".mp4", ".mkv");
+        assert!(filter_chain.is_match(&rss_item).await);
+    }
+}
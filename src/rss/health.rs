@@ -0,0 +1,68 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+/// The delay before fetching a feed again after its first failure,
+/// doubled on each consecutive failure.
+const BACKOFF_BASE_MINUTES: i64 = 5;
+/// The maximum delay between two fetches of a failing feed.
+const BACKOFF_MAX_MINUTES: i64 = 6 * 60;
+/// Feeds are disabled after this many consecutive failures, i.e. about 22.6 hours after the
+/// first one with the backoff above (5 + 10 + ... + 320 + 360 + 360 minutes).
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+
+/// The fetch health of a rss feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RssHealth {
+    pub last_attempt: DateTime<Local>,
+    pub last_success: Option<DateTime<Local>>,
+    pub consecutive_failures: u32,
+    /// The error of the last failed attempt, cleared on success
+    pub last_error: Option<String>,
+    /// The number of items parsed from the last modified content
+    pub item_count: Option<u64>,
+}
+
+impl RssHealth {
+    /// The time before which a failing feed shouldn't be fetched again.
+    pub fn next_attempt(&self) -> Option<DateTime<Local>> {
+        if self.consecutive_failures == 0 {
+            return None;
+        }
+
+        let exponent = (self.consecutive_failures - 1).min(16);
+        let minutes = (BACKOFF_BASE_MINUTES << exponent).min(BACKOFF_MAX_MINUTES);
+        Some(self.last_attempt + Duration::minutes(minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing_health(consecutive_failures: u32) -> RssHealth {
+        RssHealth {
+            last_attempt: Local::now(),
+            last_success: None,
+            consecutive_failures,
+            last_error: Some("Failed to download".to_string()),
+            item_count: None,
+        }
+    }
+
+    #[test]
+    fn test_next_attempt() {
+        let health = failing_health(0);
+        assert_eq!(health.next_attempt(), None);
+
+        let expect = [(1, 5), (2, 10), (3, 20), (7, 320), (8, 360), (100, 360)];
+        for (failures, minutes) in expect {
+            let health = failing_health(failures);
+            assert_eq!(
+                health.next_attempt(),
+                Some(health.last_attempt + Duration::minutes(minutes)),
+                "{} failures",
+                failures
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::rss::decision::DecisionReason;
+use crate::rss::filter::RssFilter;
+
+/// What was decided about a rss item the last time it was processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RssItemDecision {
+    /// Seen but not decided yet, or to be decided again
+    Pending,
+    /// Sent to the downloader
+    Downloaded,
+    /// Skipped by the filters of the rss
+    Filtered,
+    /// Failed to be parsed or sent to the downloader, retried on the next poll
+    Failed,
+}
+
+impl RssItemDecision {
+    /// Whether the item doesn't need to be processed again.
+    pub fn is_final(&self) -> bool {
+        matches!(self, RssItemDecision::Downloaded | RssItemDecision::Filtered)
+    }
+}
+
+/// A rss item seen by bt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RssItemRecord {
+    pub id: i64,
+    pub rss_id: i64,
+    pub guid: String,
+    pub title: String,
+    pub season: u64,
+    pub episode: u64,
+    pub torrent_url: String,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub decision: RssItemDecision,
+    /// The reason of the last decision
+    pub reason: Option<DecisionReason>,
+    /// The filter excluding the item
+    pub filter: Option<RssFilter>,
+    pub detail: Option<String>,
+}
@@ -0,0 +1,313 @@
+use std::fmt::Display;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+/// The media info of a release parsed from a text like
+/// `[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]`.
+///
+/// The values not recognized are left out, e.g. `[招募翻译]`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    pub source: Option<MediaSource>,
+    /// The height, e.g. `1080` of `1080p` or `1920x1080`
+    pub resolution: Option<u64>,
+    pub video_codec: Option<VideoCodec>,
+    /// Like `10` of `HEVC-10bit`
+    pub bit_depth: Option<u64>,
+    pub audio_codecs: Vec<AudioCodec>,
+    pub subtitles: Vec<SubtitleLanguage>,
+    pub subtitle_kind: Option<SubtitleKind>,
+    pub platform: Option<Platform>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(ascii_case_insensitive)]
+pub enum MediaSource {
+    #[strum(to_string = "WebRip")]
+    WebRip,
+    #[strum(to_string = "WEB-DL")]
+    WebDl,
+    #[strum(to_string = "BDRip")]
+    BdRip,
+    #[strum(to_string = "DVDRip")]
+    DvdRip,
+    #[strum(to_string = "TV")]
+    Tv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum VideoCodec {
+    Hevc,
+    Avc,
+    Av1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum AudioCodec {
+    Aac,
+    Flac,
+    #[strum(to_string = "Opus")]
+    Opus,
+    Ac3,
+    Eac3,
+    Dts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum SubtitleLanguage {
+    /// Simplified Chinese
+    Chs,
+    /// Traditional Chinese
+    Cht,
+    Jpn,
+    Eng,
+}
+
+/// How the subtitles are shipped with the video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(ascii_case_insensitive)]
+pub enum SubtitleKind {
+    /// Embedded in the container and can be turned off, e.g. `内封`
+    #[strum(to_string = "Embedded", serialize = "soft")]
+    Embedded,
+    /// Burned into the video, e.g. `内嵌`
+    #[strum(to_string = "Hardsub", serialize = "hard")]
+    Hardsub,
+}
+
+/// The streaming platform the release is ripped from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(ascii_case_insensitive)]
+pub enum Platform {
+    #[strum(to_string = "CR")]
+    Crunchyroll,
+    #[strum(to_string = "Baha")]
+    Baha,
+    #[strum(to_string = "B-Global")]
+    BGlobal,
+    #[strum(to_string = "Bilibili")]
+    Bilibili,
+    #[strum(to_string = "Netflix")]
+    Netflix,
+    #[strum(to_string = "Abema")]
+    Abema,
+    #[strum(to_string = "Amazon")]
+    Amazon,
+}
+
+static SOURCE_RES: Lazy<[(MediaSource, Regex); 5]> = Lazy::new(|| {
+    [
+        (MediaSource::WebRip, Regex::new(r"(?i)\bweb-?rip\b").unwrap()),
+        (MediaSource::WebDl, Regex::new(r"(?i)\bweb-?dl\b").unwrap()),
+        (
+            MediaSource::BdRip,
+            Regex::new(r"(?i)\bbd-?rip\b|\bbdmv\b|\bblu-?ray\b|\bbd\b").unwrap(),
+        ),
+        (MediaSource::DvdRip, Regex::new(r"(?i)\bdvd-?rip\b|\bdvd\b").unwrap()),
+        (MediaSource::Tv, Regex::new(r"(?i)\b(?:hd)?tv(?:-?rip)?\b").unwrap()),
+    ]
+});
+static RESOLUTION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:\d{3,4}x)?(\d{3,4})p?\b|\b(4k)\b").unwrap());
+static VIDEO_CODEC_RES: Lazy<[(VideoCodec, Regex); 3]> = Lazy::new(|| {
+    [
+        (VideoCodec::Hevc, Regex::new(r"(?i)hevc|x265|h\.?265|\bma10p").unwrap()),
+        (VideoCodec::Avc, Regex::new(r"(?i)avc|x264|h\.?264|\bhi10p").unwrap()),
+        (VideoCodec::Av1, Regex::new(r"(?i)\bav1\b").unwrap()),
+    ]
+});
+static BIT_DEPTH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(8|10|12)[- ]?bits?\b|\b(?:ma|hi)(10)p").unwrap());
+static AUDIO_CODEC_RES: Lazy<[(AudioCodec, Regex); 6]> = Lazy::new(|| {
+    [
+        (AudioCodec::Aac, Regex::new(r"(?i)\baac").unwrap()),
+        (AudioCodec::Flac, Regex::new(r"(?i)\bflac").unwrap()),
+        (AudioCodec::Opus, Regex::new(r"(?i)\bopus\b").unwrap()),
+        // Not the `AC3` of `E-AC3` or `EAC3`
+        (AudioCodec::Ac3, Regex::new(r"(?i)(?:^|[^\w-])ac-?3\b").unwrap()),
+        (AudioCodec::Eac3, Regex::new(r"(?i)\be-?ac-?3\b|\bddp").unwrap()),
+        (AudioCodec::Dts, Regex::new(r"(?i)\bdts").unwrap()),
+    ]
+});
+static SUBTITLE_RES: Lazy<[(SubtitleLanguage, Regex); 4]> = Lazy::new(|| {
+    [
+        (SubtitleLanguage::Chs, Regex::new(r"(?i)简|\b(chs|gb|sc)\b").unwrap()),
+        (SubtitleLanguage::Cht, Regex::new(r"(?i)繁|\b(cht|big5|tc)\b").unwrap()),
+        (SubtitleLanguage::Jpn, Regex::new(r"(?i)日|\bjpn?\b").unwrap()),
+        (SubtitleLanguage::Eng, Regex::new(r"(?i)英|\beng\b").unwrap()),
+    ]
+});
+static SUBTITLE_KIND_RES: Lazy<[(SubtitleKind, Regex); 2]> = Lazy::new(|| {
+    [
+        (SubtitleKind::Embedded, Regex::new(r"(?i)内封|\bsoftsubs?\b").unwrap()),
+        (SubtitleKind::Hardsub, Regex::new(r"(?i)内嵌|\bhardsubs?\b").unwrap()),
+    ]
+});
+static PLATFORM_RES: Lazy<[(Platform, Regex); 7]> = Lazy::new(|| {
+    [
+        (Platform::Crunchyroll, Regex::new(r"(?i)\b(?:cr|crunchyroll)\b").unwrap()),
+        (Platform::Baha, Regex::new(r"(?i)\bbaha\b|巴哈").unwrap()),
+        (Platform::BGlobal, Regex::new(r"(?i)\bb-global\b").unwrap()),
+        (Platform::Bilibili, Regex::new(r"(?i)\bbilibili\b|哔哩哔哩").unwrap()),
+        (Platform::Netflix, Regex::new(r"(?i)\b(?:netflix|nf)\b").unwrap()),
+        (Platform::Abema, Regex::new(r"(?i)\babema\b").unwrap()),
+        (Platform::Amazon, Regex::new(r"(?i)\b(?:amazon|amzn)\b").unwrap()),
+    ]
+});
+
+/// The first value whose regex matches the content.
+fn find<T: Copy>(res: &[(T, Regex)], content: &str) -> Option<T> {
+    res.iter()
+        .find(|(_, re)| re.is_match(content))
+        .map(|(value, _)| *value)
+}
+
+/// All the values whose regexes match the content.
+fn find_all<T: Copy>(res: &[(T, Regex)], content: &str) -> Vec<T> {
+    res.iter()
+        .filter(|(_, re)| re.is_match(content))
+        .map(|(value, _)| *value)
+        .collect()
+}
+
+impl MediaInfo {
+    pub fn parse(content: &str) -> Self {
+        // Underscores separate the values too, e.g. `[Ma10p_1080p][x265_flac]`.
+        let content = &content.replace('_', " ");
+        let resolution = RESOLUTION_RE.captures_iter(content).find_map(|captures| {
+            if captures.get(2).is_some() {
+                return Some(2160);
+            }
+            // A bare number is a resolution only if it's written like `1920x1080` or `1080p`.
+            let whole = captures.get(0)?.as_str();
+            let height = captures.get(1)?.as_str();
+            (whole.len() > height.len()).then_some(height)?.parse().ok()
+        });
+        let bit_depth = BIT_DEPTH_RE.captures(content).and_then(|captures| {
+            let depth = captures.get(1).or(captures.get(2))?;
+            depth.as_str().parse().ok()
+        });
+
+        Self {
+            source: find(&*SOURCE_RES, content),
+            resolution,
+            video_codec: find(&*VIDEO_CODEC_RES, content),
+            bit_depth,
+            audio_codecs: find_all(&*AUDIO_CODEC_RES, content),
+            subtitles: find_all(&*SUBTITLE_RES, content),
+            subtitle_kind: find(&*SUBTITLE_KIND_RES, content),
+            platform: find(&*PLATFORM_RES, content),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the subtitles have the tag, a language like `chs` or a kind like `embedded`
+    /// or `soft`, ignoring the case.
+    pub fn has_subtitle(&self, tag: &str) -> bool {
+        match tag.parse::<SubtitleLanguage>() {
+            Ok(language) => self.subtitles.contains(&language),
+            Err(_) => tag
+                .parse::<SubtitleKind>()
+                .is_ok_and(|kind| self.subtitle_kind == Some(kind)),
+        }
+    }
+}
+
+/// The values separated by spaces, e.g. `WebRip 1080p HEVC 10bit AAC CHS CHT JPN Embedded`.
+impl Display for MediaInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut values = Vec::new();
+        values.extend(self.source.map(|source| source.to_string()));
+        values.extend(self.resolution.map(|resolution| format!("{}p", resolution)));
+        values.extend(self.video_codec.map(|codec| codec.to_string()));
+        values.extend(self.bit_depth.map(|bit_depth| format!("{}bit", bit_depth)));
+        values.extend(self.audio_codecs.iter().map(|codec| codec.to_string()));
+        values.extend(self.subtitles.iter().map(|language| language.to_string()));
+        values.extend(self.subtitle_kind.map(|kind| kind.to_string()));
+        values.extend(self.platform.map(|platform| platform.to_string()));
+        f.write_str(&values.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_media_info() {
+        let info = MediaInfo::parse("[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]");
+        assert_eq!(
+            info,
+            MediaInfo {
+                source: Some(MediaSource::WebRip),
+                resolution: Some(1080),
+                video_codec: Some(VideoCodec::Hevc),
+                bit_depth: Some(10),
+                audio_codecs: vec![AudioCodec::Aac],
+                subtitles: vec![
+                    SubtitleLanguage::Chs,
+                    SubtitleLanguage::Cht,
+                    SubtitleLanguage::Jpn
+                ],
+                subtitle_kind: Some(SubtitleKind::Embedded),
+                platform: None,
+            }
+        );
+        assert_eq!(info.to_string(), "WebRip 1080p HEVC 10bit AAC CHS CHT JPN Embedded");
+
+        let info = MediaInfo::parse("(B-Global 1920x1080 HEVC AAC MKV)");
+        assert_eq!(info.resolution, Some(1080));
+        assert_eq!(info.video_codec, Some(VideoCodec::Hevc));
+        assert_eq!(info.platform, Some(Platform::BGlobal));
+
+        let info = MediaInfo::parse("[1080P][Baha][WEB-DL][AAC AVC][CHT]");
+        assert_eq!(info.source, Some(MediaSource::WebDl));
+        assert_eq!(info.resolution, Some(1080));
+        assert_eq!(info.subtitles, vec![SubtitleLanguage::Cht]);
+        assert_eq!(info.video_codec, Some(VideoCodec::Avc));
+        assert_eq!(info.platform, Some(Platform::Baha));
+
+        let info = MediaInfo::parse("[4K][简体内嵌]");
+        assert_eq!(info.resolution, Some(2160));
+        assert_eq!(info.subtitles, vec![SubtitleLanguage::Chs]);
+        assert_eq!(info.subtitle_kind, Some(SubtitleKind::Hardsub));
+
+        let info = MediaInfo::parse("[BDRip 1920x1080 x264 FLAC E-AC3][简日双语]");
+        assert_eq!(info.source, Some(MediaSource::BdRip));
+        assert_eq!(info.audio_codecs, vec![AudioCodec::Flac, AudioCodec::Eac3]);
+        assert_eq!(info.subtitles, vec![SubtitleLanguage::Chs, SubtitleLanguage::Jpn]);
+
+        let info = MediaInfo::parse("[Ma10p_1080p][x265_flac_ac3]");
+        assert_eq!(info.video_codec, Some(VideoCodec::Hevc));
+        assert_eq!(info.bit_depth, Some(10));
+        assert_eq!(info.audio_codecs, vec![AudioCodec::Flac, AudioCodec::Ac3]);
+
+        let info = MediaInfo::parse("(CR 1920x1080 AVC AAC MKV)");
+        assert_eq!(info.platform, Some(Platform::Crunchyroll));
+        assert_eq!(info.to_string(), "1080p AVC AAC CR");
+
+        assert_eq!(MediaInfo::parse("(1080p) [C3C9B8A4]").resolution, Some(1080));
+        assert!(MediaInfo::parse("[招募翻译]").is_empty());
+    }
+
+    #[test]
+    fn test_has_subtitle() {
+        let info = MediaInfo::parse("[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]");
+        assert!(info.has_subtitle("chs"));
+        assert!(info.has_subtitle("JPN"));
+        assert!(info.has_subtitle("embedded"));
+        assert!(info.has_subtitle("soft"));
+        assert!(!info.has_subtitle("hard"));
+        assert!(!info.has_subtitle("eng"));
+        assert!(!info.has_subtitle("unknown"));
+    }
+}
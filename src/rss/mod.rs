@@ -1,13 +1,22 @@
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 use typed_builder::TypedBuilder;
 
 use crate::downloader::TorrentMeta;
-use crate::renamer::BangumiInfo;
+use crate::renamer::{BangumiInfo, EpisodeRange};
 use crate::rss::filter::RssFilterChain;
+use crate::rss::health::RssHealth;
+use crate::rss::media_info::MediaInfo;
 
-mod filter;
+pub mod decision;
+pub mod filter;
+pub mod health;
+pub mod ledger;
+pub mod media_info;
 pub mod parsers;
+pub mod score;
 pub mod store;
 
 #[derive(Debug, Clone, TypedBuilder, Serialize, Deserialize)]
@@ -29,10 +38,43 @@ pub struct Rss {
     pub enabled: Option<bool>,
     #[builder(default)]
     pub filters: Option<RssFilterChain>,
+    /// Don't apply the global filters to the rss
+    #[serde(default)]
+    #[builder(default)]
+    pub skip_global_filters: bool,
+    /// Hold the releases of an episode for the minutes since the first one is seen,
+    /// then download the best scored one. `None` downloads every accepted release.
+    #[builder(default)]
+    pub wait_minutes: Option<u64>,
+    /// What to do with an episode already downloaded, from any rss
+    #[serde(default)]
+    #[builder(default)]
+    pub duplicate_policy: DuplicatePolicy,
+    /// Don't name the files by the episode titles parsed from the items, e.g. `愚物语`
+    /// of `物语系列 - 01 愚物语 [WebRip 1080p]`
+    #[serde(default)]
+    #[builder(default)]
+    pub skip_episode_titles: bool,
     #[builder(default)]
     pub description: Option<String>,
     #[builder(default)]
     pub category: Option<String>,
+    /// The validators of the last fetched content, maintained by the poller
+    #[serde(skip)]
+    #[builder(default)]
+    pub http_cache: HttpCache,
+    /// The fetch health of the rss, `None` if it's never fetched
+    #[serde(default, skip_deserializing)]
+    #[builder(default)]
+    pub health: Option<RssHealth>,
+}
+
+/// The `ETag` and `Last-Modified` headers of a fetched feed, sent back as
+/// `If-None-Match` and `If-Modified-Since` to skip unchanged feeds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpCache {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
 #[allow(unused)]
@@ -41,6 +83,30 @@ pub struct Rss {
 #[serde(rename_all = "snake_case")]
 pub enum RssType {
     Mikan,
+    Generic,
+    Nyaa,
+    Dmhy,
+    AcgRip,
+    Torznab,
+}
+
+/// What to do with a release of an episode already downloaded.
+///
+/// Episodes are the same if their season, episode and show name ignoring the case
+/// and punctuations are the same, see [`BangumiInfo::normalized_show_name`].
+#[derive(
+    Debug, Default, PartialEq, Eq, Clone, Copy, Display, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// Skip the episode if it's downloaded
+    Skip,
+    /// Download the release if it scores higher than the downloaded ones
+    PreferHigherScore,
+    /// Download every release, e.g. both the CHS and CHT releases of a fansub
+    #[default]
+    Always,
 }
 
 /// The rss subscription content struct
@@ -48,24 +114,93 @@ pub enum RssType {
 pub struct RssSubscription {
     pub url: String,
     pub items: Vec<RssSubscriptionItem>,
+    /// The items failed to be parsed
+    #[serde(default)]
+    pub unparsed: Vec<UnparsedItem>,
+}
+
+/// A rss item failed to be parsed, e.g. the episode is not recognized
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnparsedItem {
+    pub title: String,
+    /// The item url, or the torrent url if the feed doesn't provide one, empty if neither
+    #[serde(default)]
+    pub guid: String,
+    pub error: String,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, TypedBuilder)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, TypedBuilder)]
 pub struct RssSubscriptionItem {
     pub url: String,
     pub title: String,
+    /// The title of the episode after the episode number, empty if there's none
     pub episode_title: String,
     pub season: u64,
     pub episode: u64,
+    /// The label of a special episode like `SP2`, `OVA`, `00` or `13.5`, laid out in the
+    /// specials of the show, i.e. season 0
+    #[builder(default)]
+    pub special: Option<String>,
+    /// The revision of a re-released episode, like `2` of `19v2`, `None` for the original release
+    #[builder(default)]
+    pub revision: Option<u64>,
+    /// The episodes of a batch release like `[01-12]`, `episode` is the first one
+    #[builder(default)]
+    pub batch: Option<EpisodeRange>,
     pub fansub: String,
+    /// The media info text like `[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]`,
+    /// see [`RssSubscriptionItem::parsed_media_info`]
     pub media_info: String,
     pub torrent: TorrentMeta,
     pub category: String,
+    /// The category provided by the feed like `Anime - English-translated` of nyaa or the
+    /// comma separated categories of torznab, only used by the filters, empty if there's none
+    #[builder(default)]
+    pub feed_category: String,
+    /// The total size of the torrent in bytes, if the feed provides it
+    #[builder(default)]
+    pub size: Option<u64>,
+    /// The number of seeders, if the feed provides it
+    #[builder(default)]
+    pub seeders: Option<u64>,
+    /// Whether the uploader is trusted, if the feed provides it like nyaa
+    #[builder(default)]
+    pub trusted: Option<bool>,
+    /// The publication date, if the feed provides it
+    #[builder(default)]
+    pub pub_date: Option<DateTime<Local>>,
 }
 
+impl RssSubscriptionItem {
+    /// The identity of the item in its rss: the item url, or the torrent url
+    /// if the feed doesn't provide one.
+    pub fn guid(&self) -> &str {
+        if self.url.is_empty() {
+            &self.torrent.url
+        } else {
+            &self.url
+        }
+    }
+
+    pub fn parsed_media_info(&self) -> MediaInfo {
+        MediaInfo::parse(&self.media_info)
+    }
+}
+
+/// Name the files by the parsed media info rather than the media info text, e.g.
+/// `[LoliHouse][WebRip 1080p HEVC 10bit AAC CHS CHT JPN Embedded]`, see `RENAME_MEDIA_INFO`.
+static RENAME_MEDIA_INFO: Lazy<bool> = Lazy::new(|| {
+    std::env::var("RENAME_MEDIA_INFO").is_ok_and(|rename_media_info| rename_media_info == "true")
+});
+
 impl From<&RssSubscriptionItem> for BangumiInfo {
     fn from(s: &RssSubscriptionItem) -> Self {
-        let display_name = format!("{}{}", s.fansub, s.media_info);
+        let media_info = s.parsed_media_info();
+        let display_name = if *RENAME_MEDIA_INFO && !media_info.is_empty() {
+            format!("{}[{}]", s.fansub, media_info)
+        } else {
+            format!("{}{}", s.fansub, s.media_info)
+        };
 
         BangumiInfo::builder()
             .show_name(s.title.clone())
@@ -88,6 +223,9 @@ impl From<&RssSubscriptionItem> for BangumiInfo {
             })
             .season(s.season)
             .episode(s.episode)
+            .batch(s.batch)
+            .special(s.special.clone())
+            .media_info(Some(media_info))
             .category({
                 if s.category.is_empty() {
                     None
@@ -117,6 +255,7 @@ mod tests {
             torrent: crate::downloader::TorrentMeta::builder()
                 .url("https://mikanani.me/Download/20240118/059724511d60173251b378b04709aceff92fffb5.torrent".to_string())
                 .build(),
+            ..Default::default()
         };
 
         let bangumi_info: BangumiInfo = (&rss_item).into();
@@ -130,5 +269,8 @@ mod tests {
         );
         assert_eq!(bangumi_info.season, 1);
         assert_eq!(bangumi_info.episode, 18);
+        let media_info = bangumi_info.media_info.unwrap();
+        assert_eq!(media_info.resolution, Some(1080));
+        assert_eq!(media_info.bit_depth, Some(10));
     }
 }
@@ -0,0 +1,132 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::{
+    build_rss_item, parse_pub_date, strip_file_extension, ParsingError, RssParser,
+};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
+
+/// Normalize the acg.rip title conventions before parsing:
+/// - the title is often the released file name, e.g. `... [CHT].mp4`
+/// - the media info may be wrapped with full-width parentheses, e.g. `（B-Global 1920x1080）`
+fn pretreat_acgrip_title(title: &str) -> String {
+    strip_file_extension(title)
+        .replace('（', "(")
+        .replace('）', ")")
+}
+
+fn parse_rss_item(rss: &Rss, item: &AcgRipRssItem) -> Result<RssSubscriptionItem, ParsingError> {
+    let torrent_url = item
+        .enclosure
+        .as_ref()
+        .map(|enclosure| enclosure.url.trim())
+        .ok_or_else(|| {
+            ParsingError::InvalidRss(format!("No torrent found in rss item: {}", item.title))
+        })?;
+
+    let torrent = TorrentMeta::builder().url(torrent_url.to_string()).build();
+    let title = pretreat_acgrip_title(&item.title);
+    let mut rss_item = build_rss_item(rss, item.link.trim(), &title, torrent)?;
+    rss_item.pub_date = item.pub_date.as_deref().and_then(parse_pub_date);
+    Ok(rss_item)
+}
+
+/// The parser for acg.rip feeds.
+#[derive(Default)]
+pub struct AcgRipParser {}
+
+impl AcgRipParser {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl RssParser for AcgRipParser {
+    fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+        let rss_xml: AcgRipRss =
+            serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+        let mut rss_items = Vec::new();
+        let mut unparsed = Vec::new();
+        for item in rss_xml.channel.item {
+            match parse_rss_item(rss, &item) {
+                Ok(rss_item) => rss_items.push(rss_item),
+                Err(err) => {
+                    error!("[parser] {}", err);
+                    unparsed.push(UnparsedItem {
+                        title: item.title.clone(),
+                        guid: item.link.trim().to_string(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(RssSubscription {
+            url: rss.url.clone(),
+            items: rss_items,
+            unparsed,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AcgRipRss {
+    channel: AcgRipRssChannel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AcgRipRssChannel {
+    #[serde(default)]
+    item: Vec<AcgRipRssItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AcgRipRssItem {
+    title: String,
+    link: String,
+    #[serde(default)]
+    enclosure: Option<AcgRipEnclosure>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AcgRipEnclosure {
+    url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::RssType;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_parse_rss_content() {
+        let rss_content = read_to_string("./tests/dataset/acgrip-1.rss").unwrap();
+
+        let rss = Rss::builder()
+            .url("https://acg.rip/.xml?term=Dungeon+Meshi".to_string())
+            .rss_type(RssType::AcgRip)
+            .build();
+        let parser = AcgRipParser::new();
+        let res = parser.parse_content(&rss, &rss_content).unwrap();
+
+        assert_eq!(res.items.len(), 2);
+
+        let item = &res.items[0];
+        assert_eq!(item.url, "https://acg.rip/t/301234");
+        assert_eq!(item.title, "Dungeon Meshi");
+        assert_eq!(item.episode, 14);
+        assert_eq!(item.fansub, "[ANi]");
+        assert_eq!(item.media_info, "[1080P][Baha][WEB-DL][AAC AVC][CHT]");
+        assert_eq!(item.torrent.url, "https://acg.rip/t/301234.torrent");
+
+        let item = &res.items[1];
+        assert_eq!(item.title, "迷宫饭");
+        assert_eq!(item.fansub, "[NC-Raws]");
+        assert_eq!(item.media_info, "(B-Global 1920x1080 HEVC AAC MKV)");
+    }
+}
@@ -0,0 +1,130 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::{build_rss_item, parse_pub_date, ParsingError, RssParser};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
+
+fn parse_rss_item(rss: &Rss, item: &DmhyRssItem) -> Result<RssSubscriptionItem, ParsingError> {
+    // dmhy only provides the magnet link in the enclosure.
+    let magnet = item
+        .enclosure
+        .as_ref()
+        .map(|enclosure| enclosure.url.trim())
+        .filter(|url| url.starts_with("magnet:"))
+        .ok_or_else(|| {
+            ParsingError::InvalidRss(format!("No magnet link found in rss item: {}", item.title))
+        })?;
+
+    let torrent = TorrentMeta::builder().url(magnet.to_string()).build();
+    let mut rss_item = build_rss_item(rss, item.link.trim(), item.title.trim(), torrent)?;
+    rss_item.pub_date = item.pub_date.as_deref().and_then(parse_pub_date);
+    Ok(rss_item)
+}
+
+/// The parser for dmhy (share.dmhy.org) feeds.
+#[derive(Default)]
+pub struct DmhyParser {}
+
+impl DmhyParser {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl RssParser for DmhyParser {
+    fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+        let rss_xml: DmhyRss =
+            serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+        let mut rss_items = Vec::new();
+        let mut unparsed = Vec::new();
+        for item in rss_xml.channel.item {
+            match parse_rss_item(rss, &item) {
+                Ok(rss_item) => rss_items.push(rss_item),
+                Err(err) => {
+                    error!("[parser] {}", err);
+                    unparsed.push(UnparsedItem {
+                        title: item.title.clone(),
+                        guid: item.link.trim().to_string(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(RssSubscription {
+            url: rss.url.clone(),
+            items: rss_items,
+            unparsed,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DmhyRss {
+    channel: DmhyRssChannel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DmhyRssChannel {
+    #[serde(default)]
+    item: Vec<DmhyRssItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DmhyRssItem {
+    title: String,
+    link: String,
+    #[serde(default)]
+    enclosure: Option<DmhyEnclosure>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DmhyEnclosure {
+    url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::RssType;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_parse_rss_content() {
+        let rss_content = read_to_string("./tests/dataset/dmhy-1.rss").unwrap();
+
+        let rss = Rss::builder()
+            .url("https://share.dmhy.org/topics/rss/rss.xml".to_string())
+            .rss_type(RssType::Dmhy)
+            .build();
+        let parser = DmhyParser::new();
+        let res = parser.parse_content(&rss, &rss_content).unwrap();
+
+        assert_eq!(res.items.len(), 2);
+
+        let item = &res.items[0];
+        assert_eq!(
+            item.url,
+            "http://share.dmhy.org/topics/view/667025_LoliHouse_Dungeon_Meshi_-_14.html"
+        );
+        assert_eq!(item.title, "迷宫饭");
+        assert_eq!(item.episode, 14);
+        assert_eq!(item.fansub, "[喵萌奶茶屋&LoliHouse]");
+        assert_eq!(item.media_info, "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]");
+        assert_eq!(
+            item.torrent.url,
+            "magnet:?xt=urn:btih:LKZW5VJQ7YAV3ZNXKJ2XN3GPQ4RWCPRX&dn=&tr=http%3A%2F%2Ft.acg.rip%3A6699%2Fannounce"
+        );
+
+        let item = &res.items[1];
+        assert_eq!(item.title, "夜晚的水母不会游泳");
+        assert_eq!(item.episode, 1);
+        assert_eq!(item.fansub, "[喵萌奶茶屋]");
+        assert_eq!(item.media_info, "[1080p][简日双语][招募翻译]");
+        assert!(item.torrent.url.starts_with("magnet:?xt=urn:btih:4a5b0b4a"));
+    }
+}
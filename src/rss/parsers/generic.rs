@@ -0,0 +1,347 @@
+use log::error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::{build_rss_item, parse_pub_date, ParsingError, RssParser};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
+
+/// Find the first magnet link in a piece of text, e.g. an item description.
+fn find_magnet(content: &str) -> Option<String> {
+    let re = Regex::new(r#"magnet:\?[^\s"'<>]+"#).unwrap();
+    re.find(content).map(|m| m.as_str().replace("&amp;", "&"))
+}
+
+/// Whether the url points to a torrent file or a magnet link.
+fn is_torrent_url(url: &str) -> bool {
+    let path = url.split(&['?', '#'][..]).next().unwrap_or(url);
+    url.starts_with("magnet:") || path.to_lowercase().ends_with(".torrent")
+}
+
+/// The guid of an item, see [`RssSubscriptionItem::guid`].
+fn item_guid(link: &str, torrent_url: &Option<String>) -> String {
+    if link.is_empty() {
+        torrent_url.clone().unwrap_or_default()
+    } else {
+        link.to_string()
+    }
+}
+
+fn build_generic_item(
+    rss: &Rss,
+    url: &str,
+    title: &str,
+    torrent_url: Option<String>,
+    pub_date: Option<&str>,
+) -> Result<RssSubscriptionItem, ParsingError> {
+    let torrent_url = torrent_url.ok_or_else(|| {
+        ParsingError::InvalidRss(format!("No torrent found in rss item: {}", title))
+    })?;
+
+    let mut rss_item =
+        build_rss_item(rss, url, title, TorrentMeta::builder().url(torrent_url).build())?;
+    rss_item.pub_date = pub_date.and_then(parse_pub_date);
+    Ok(rss_item)
+}
+
+fn parse_rss_2_0(rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+    let rss_xml: GenericRss =
+        serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+    let mut rss_items = Vec::new();
+    let mut unparsed = Vec::new();
+    for item in rss_xml.channel.item {
+        let link = item.link.as_deref().map(str::trim).unwrap_or_default();
+
+        // PRIORITY: enclosure > link > magnet in description
+        let torrent_url = item
+            .enclosure
+            .as_ref()
+            .map(|enclosure| enclosure.url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .or_else(|| Some(link.to_string()).filter(|url| is_torrent_url(url)))
+            .or_else(|| item.description.as_deref().and_then(find_magnet));
+
+        let guid = item_guid(link, &torrent_url);
+        let pub_date = item.pub_date.as_deref();
+        match build_generic_item(rss, link, item.title.trim(), torrent_url, pub_date) {
+            Ok(rss_item) => rss_items.push(rss_item),
+            Err(err) => {
+                error!("[parser] {}", err);
+                unparsed.push(UnparsedItem {
+                    title: item.title.trim().to_string(),
+                    guid,
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+
+    // `<atom:link rel="self">` shares the local name with `<link>`, so keep the textual one.
+    let url = rss_xml
+        .channel
+        .link
+        .iter()
+        .map(|link| link.value.trim())
+        .find(|link| !link.is_empty())
+        .unwrap_or_default();
+
+    Ok(RssSubscription {
+        url: url.to_string(),
+        items: rss_items,
+        unparsed,
+    })
+}
+
+fn parse_atom(rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+    let feed: AtomFeed =
+        serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+    let mut rss_items = Vec::new();
+    let mut unparsed = Vec::new();
+    for entry in feed.entry {
+        let link = entry
+            .link
+            .iter()
+            .find(|link| link.rel.as_deref().unwrap_or("alternate") == "alternate")
+            .map(|link| link.href.trim())
+            .unwrap_or_default();
+
+        // PRIORITY: enclosure link > torrent link > magnet in content
+        let torrent_url = entry
+            .link
+            .iter()
+            .find(|link| {
+                link.rel.as_deref() == Some("enclosure")
+                    || link.link_type.as_deref() == Some("application/x-bittorrent")
+            })
+            .or_else(|| {
+                entry
+                    .link
+                    .iter()
+                    .find(|link| is_torrent_url(link.href.trim()))
+            })
+            .map(|link| link.href.trim().to_string())
+            .or_else(|| {
+                entry
+                    .content
+                    .as_deref()
+                    .or(entry.summary.as_deref())
+                    .and_then(find_magnet)
+            });
+
+        let guid = item_guid(link, &torrent_url);
+        let pub_date = entry.published.as_deref().or(entry.updated.as_deref());
+        match build_generic_item(rss, link, entry.title.trim(), torrent_url, pub_date) {
+            Ok(rss_item) => rss_items.push(rss_item),
+            Err(err) => {
+                error!("[parser] {}", err);
+                unparsed.push(UnparsedItem {
+                    title: entry.title.trim().to_string(),
+                    guid,
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+
+    let url = feed
+        .link
+        .first()
+        .map(|link| link.href.trim().to_string())
+        .unwrap_or_default();
+
+    Ok(RssSubscription {
+        url,
+        items: rss_items,
+        unparsed,
+    })
+}
+
+/// The parser for standard RSS 2.0 and Atom feeds.
+///
+/// The torrent of each item is read from `<enclosure>`, `<link>`, or a magnet link
+/// in the item description, and the title is parsed like a mikan item title.
+#[derive(Default)]
+pub struct GenericParser {}
+
+impl GenericParser {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl RssParser for GenericParser {
+    fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+        let root_re = Regex::new(r"<(rss|feed)[\s>]").unwrap();
+        match root_re
+            .captures(content)
+            .map(|c| c.get(1).unwrap().as_str())
+        {
+            Some("rss") => parse_rss_2_0(rss, content),
+            Some("feed") => parse_atom(rss, content),
+            _ => Err(ParsingError::InvalidRss("Neither an RSS 2.0 nor an Atom feed".to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GenericRss {
+    channel: GenericRssChannel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GenericRssChannel {
+    #[serde(default)]
+    link: Vec<GenericChannelLink>,
+    #[serde(default)]
+    item: Vec<GenericRssItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GenericChannelLink {
+    #[serde(rename = "$value", default)]
+    value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GenericRssItem {
+    title: String,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    enclosure: Option<GenericEnclosure>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GenericEnclosure {
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AtomFeed {
+    #[serde(default)]
+    link: Vec<AtomLink>,
+    #[serde(default)]
+    entry: Vec<AtomEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AtomEntry {
+    title: String,
+    #[serde(default)]
+    link: Vec<AtomLink>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    published: Option<String>,
+    #[serde(default)]
+    updated: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct AtomLink {
+    href: String,
+    #[serde(default)]
+    rel: Option<String>,
+    #[serde(rename = "type", default)]
+    link_type: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::RssType;
+    use std::fs::read_to_string;
+
+    fn empty_rss() -> Rss {
+        Rss::builder()
+            .url("".to_string())
+            .rss_type(RssType::Generic)
+            .build()
+    }
+
+    #[test]
+    fn test_parse_rss_2_0() {
+        let rss_content = read_to_string("./tests/dataset/generic-1.rss").unwrap();
+
+        let parser = GenericParser::new();
+        let res = parser.parse_content(&empty_rss(), &rss_content).unwrap();
+
+        assert_eq!(res.url, "https://example.org/lolihouse");
+        // The recruitment notice has no torrent and is skipped.
+        assert_eq!(res.items.len(), 3);
+
+        let expect = [
+            (
+                "https://example.org/lolihouse/view/1018",
+                18,
+                "https://example.org/lolihouse/download/1018.torrent",
+            ),
+            (
+                "https://example.org/lolihouse/download/1017.torrent",
+                17,
+                "https://example.org/lolihouse/download/1017.torrent",
+            ),
+            (
+                "https://example.org/lolihouse/view/1016",
+                16,
+                "magnet:?xt=urn:btih:f5ee4fdd7d0a9e2a9f5b1d5c6a4c4b0e0a1d2c3b&dn=Frieren+16",
+            ),
+        ];
+        for (item, (url, episode, torrent_url)) in res.items.iter().zip(expect.iter()) {
+            assert_eq!(item.url, *url);
+            assert_eq!(item.title, "葬送的芙莉莲");
+            assert_eq!(item.season, 1);
+            assert_eq!(item.episode, *episode);
+            assert_eq!(item.fansub, "[LoliHouse]");
+            assert_eq!(item.media_info, "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]");
+            assert_eq!(item.torrent.url, *torrent_url);
+        }
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let rss_content = read_to_string("./tests/dataset/generic-atom.xml").unwrap();
+
+        let rss = Rss::builder()
+            .url("".to_string())
+            .rss_type(RssType::Generic)
+            .title(Some("摇曳露营△".to_string()))
+            .category(Some("Anime".to_string()))
+            .build();
+        let parser = GenericParser::new();
+        let res = parser.parse_content(&rss, &rss_content).unwrap();
+
+        assert_eq!(res.url, "https://example.org/upto21");
+        assert_eq!(res.items.len(), 2);
+
+        assert_eq!(res.items[0].url, "https://example.org/upto21/view/301");
+        assert_eq!(res.items[0].title, "摇曳露营△");
+        assert_eq!(res.items[0].category, "Anime");
+        assert_eq!(res.items[0].season, 3);
+        assert_eq!(res.items[0].episode, 1);
+        assert_eq!(res.items[0].fansub, "[Up to 21°C]");
+        assert_eq!(res.items[0].media_info, "(CR 1920x1080 AVC AAC MKV)");
+        assert_eq!(res.items[0].torrent.url, "https://example.org/upto21/download/301.torrent");
+        assert_eq!(res.items[0].pub_date, parse_pub_date("2024-04-04T15:30:00Z"));
+
+        assert_eq!(res.items[1].episode, 2);
+        assert_eq!(
+            res.items[1].torrent.url,
+            "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Yuru+Camp+S3+02"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_feed() {
+        let parser = GenericParser::new();
+        assert!(parser.parse_content(&empty_rss(), "<html></html>").is_err());
+    }
+}
@@ -3,8 +3,10 @@ use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 
 use crate::downloader::TorrentMeta;
+use crate::renamer::EpisodeRange;
+use crate::rss::parsers::season::{detect_season, SeasonConfidence, SeasonMatch};
 use crate::rss::parsers::RssParser;
-use crate::rss::{Rss, RssSubscription, RssSubscriptionItem};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
 
 /// Parse the rss item info from the rss item title.
 ///
@@ -15,11 +17,29 @@ use crate::rss::{Rss, RssSubscription, RssSubscriptionItem};
 /// - fansub
 /// - title
 /// - season(optional)
-/// - episode
+/// - episode, or a special episode like `SP2`, `OVA`, `00` or `13.5`
+/// - revision(optional), e.g. `2` of `19v2`
+/// - episode title(optional), e.g. `愚物语` of `01 愚物语`
+/// - batch(optional), e.g. `01-12` of a batch release, the episode is the first one
 /// - media_info
-fn parse_rss_item_info(content: &str) -> Option<(String, String, u64, u64, String)> {
+///
+/// Only these fields of the returned item are set.
+pub(crate) fn parse_rss_item_info(content: &str) -> Option<RssSubscriptionItem> {
     let content = pretreat_rss_item_title(content.to_string());
 
+    // A batch release may look like a regular one, e.g. `title - 01-13 Fin [media_info]`.
+    if let Some((fansub, title, season, batch, media_info)) = parse_batch(&content) {
+        return Some(RssSubscriptionItem {
+            fansub,
+            title,
+            season,
+            episode: batch.first,
+            batch: Some(batch),
+            media_info,
+            ..Default::default()
+        });
+    }
+
     // Parsing each item using standard(maybe) format, the result is always correct.
     match split_by_regular_format(&content) {
         Some(captures) => {
@@ -29,15 +49,20 @@ fn parse_rss_item_info(content: &str) -> Option<(String, String, u64, u64, Strin
                 .map_or("", |m| m.as_str())
                 .to_string();
             // 葬送的芙莉莲 / Sousou no Frieren
-            let (title, season) =
-                parse_bangumi_title_and_season(captures.name("title").map_or("", |m| m.as_str()));
-            // 17
-            let episode = captures
-                .name("episode")
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
+            let SeasonMatch { title, season, .. } =
+                detect_season(captures.name("title").map_or("", |m| m.as_str()));
+            // 17, or SP2
+            let (episode, special) = parse_episode(captures.name("episode").unwrap().as_str())?;
+            // Specials are laid out in season 0.
+            let season = if special.is_some() { 0 } else { season };
+            // 2 of 19v2
+            let revision = captures
+                .name("revision")
+                .map(|m| m.as_str().parse::<u64>().unwrap());
+            // 愚物语
+            let episode_title = captures
+                .name("episode_name")
+                .map_or(String::new(), |m| parse_episode_title(m.as_str()));
             // [WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]
             let media_info = captures
                 .name("media")
@@ -46,19 +71,89 @@ fn parse_rss_item_info(content: &str) -> Option<(String, String, u64, u64, Strin
 
             let title = remove_redundant_brackets(&title);
 
-            Some((fansub, title, season, episode, media_info))
+            Some(RssSubscriptionItem {
+                fansub,
+                title,
+                episode_title,
+                season,
+                episode,
+                special,
+                revision,
+                media_info,
+                ..Default::default()
+            })
         }
         None => {
             // If it is fail, fallback to parse every part only and drop some info
             // because we can get them later from database alternatively.
             let (fansub, title, season) = parse_fansub_title_season(&content)?;
-            let (episode, media_info) = parse_episode_num_and_media_info(&content)?;
-
-            Some((fansub, title, season, episode, media_info))
+            let (episode, special, revision, media_info) =
+                parse_episode_num_and_media_info(&content)?;
+            let season = if special.is_some() { 0 } else { season };
+
+            Some(RssSubscriptionItem {
+                fansub,
+                title,
+                season,
+                episode,
+                special,
+                revision,
+                media_info,
+                ..Default::default()
+            })
         }
     }
 }
 
+/// Parse an episode like `17`, or a special episode like `SP2`, `OVA`, `00` or `13.5`
+/// into its number in the specials and its label.
+fn parse_episode(content: &str) -> Option<(u64, Option<String>)> {
+    let content = content.trim();
+    let special_re =
+        Regex::new(r"(?i)^(?:SP|OVA|OAD|Extra|特别篇|总集篇)\s?(?<number>\d+)?$").unwrap();
+
+    if let Ok(episode) = content.parse::<u64>() {
+        // `00` is usually a prologue aired before the first episode.
+        let special = (episode == 0).then(|| content.to_string());
+        return Some((episode, special));
+    }
+    if let Some(captures) = special_re.captures(content) {
+        let number = match captures.name("number") {
+            Some(number) => number.as_str().parse().ok()?,
+            None => 1,
+        };
+        return Some((number, Some(content.to_string())));
+    }
+    // `13.5` is usually a recap between the 13th and the 14th episodes.
+    let (episode, fraction) = content.split_once('.')?;
+    if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((episode.parse().ok()?, Some(content.to_string())))
+}
+
+/// Clean the episode title after the episode, e.g. `愚物语` of `- 01 愚物语 [WebRip 1080p]`.
+///
+/// The media info in brackets like `（B-Global 1920x1080）` and the ending markers like
+/// `END` aren't an episode title.
+fn parse_episode_title(content: &str) -> String {
+    let brackets_re = Regex::new(r"[\[(（【][^\])）】]*[\])）】]").unwrap();
+    let title = brackets_re.replace_all(content, "");
+    let title = title.trim();
+    // 「冒险的终点」
+    let title = [('「', '」'), ('『', '』'), ('"', '"')]
+        .iter()
+        .find_map(|&(open, close)| title.strip_prefix(open)?.strip_suffix(close))
+        .unwrap_or(title)
+        .trim();
+
+    let ending_re = Regex::new(r"(?i)^(?:end|fin|final|完结?|终)$").unwrap();
+    if ending_re.is_match(title) {
+        return String::new();
+    }
+    title.to_string()
+}
+
 fn parse_rss_item_torrent(item: &MikanRssItem) -> TorrentMeta {
     TorrentMeta::builder()
         .url(item.enclosure.url.clone())
@@ -86,23 +181,13 @@ fn pretreat_rss_item_title(title: String) -> String {
 }
 
 fn parse_rss_item(item: &MikanRssItem) -> Result<RssSubscriptionItem, super::ParsingError> {
-    let builder = RssSubscriptionItem::builder()
-        .url(item.link.clone())
-        .episode_title("".to_string());
-
     match parse_rss_item_info(&item.title) {
-        Some((fansub, title, season, episode, media_info)) => {
-            let torrent = parse_rss_item_torrent(item);
-            Ok(builder
-                .fansub(fansub)
-                .title(title)
-                .season(season)
-                .episode(episode)
-                .media_info(media_info)
-                .torrent(torrent)
-                .category("".to_string())
-                .build())
-        }
+        Some(info) => Ok(RssSubscriptionItem {
+            url: item.link.clone(),
+            torrent: parse_rss_item_torrent(item),
+            pub_date: super::parse_pub_date(&item.torrent.pub_date),
+            ..info
+        }),
         None => Err(super::ParsingError::UnrecognizedEpisode(format!(
             "Failed to parse rss item: {:?}",
             item
@@ -110,46 +195,14 @@ fn parse_rss_item(item: &MikanRssItem) -> Result<RssSubscriptionItem, super::Par
     }
 }
 
-fn parse_bangumi_title_and_season(content: &str) -> (String, u64) {
-    let title_season_re =
-        Regex::new(r"([^\[^\]]*)\s第([一|二|三|四|五|六|七|八|九|十]+)季").unwrap();
-    let (titles, season) = match title_season_re.captures(content) {
-        Some(captures) => {
-            let titles = captures.get(1).unwrap().as_str();
-            let season = captures.get(2).unwrap().as_str();
-            let season = match season {
-                "一" => 1,
-                "二" => 2,
-                "三" => 3,
-                "四" => 4,
-                "五" => 5,
-                "六" => 6,
-                "七" => 7,
-                "八" => 8,
-                "九" => 9,
-                "十" => 10,
-                _ => unimplemented!("implemented season number"),
-            };
-
-            (titles, season)
-        }
-        None => (content, 1),
-    };
-
-    let titles: Vec<_> = titles.split(&['/', '|'][..]).collect();
-    let title = titles[0].trim();
-
-    (title.to_owned(), season)
-}
-
 // e.g. [喵萌奶茶屋&amp;LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 17 [WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]
 const PATTERN_REGULAR_TITLE: &str = r"^(?x) # enable extend mode
 (?<fansub>\[.*?\])
 \s*
 (?<title>.*?)
 \s*\-\s*
-(?<episode>\d+)
-(?:v\d)?
+(?<episode>\d+(?:\.\d+)?|(?i:SP|OVA|OAD|Extra)(?:\s?\d+)?\b)
+(?:v(?<revision>\d+))?
 \s*
 (?<episode_name>.*?)?
 \s*
@@ -163,6 +216,77 @@ fn split_by_regular_format(title: &str) -> Option<Captures> {
     return re.captures(title);
 }
 
+/// Parse a batch release title, e.g.
+/// `[LoliHouse] 迷宫饭 / Dungeon Meshi [01-24 合集][WebRip 1080p HEVC-10bit AAC]`.
+///
+/// The episodes are a range like `01-12` or `01-13 Fin`, or a word like `合集` or
+/// `Complete` for all the episodes. The title is the part before the episodes or the
+/// first bracket, or the first bracket if there is nothing before.
+fn parse_batch(content: &str) -> Option<(String, String, u64, EpisodeRange, String)> {
+    let range_re = Regex::new(
+        r"(?i)(?:^|[\s\[\(])(?<first>\d{1,4})[-~～](?<last>\d{1,4})(?:\s*(?:fin|end|完结?|合集|全集))?(?:$|[\s\]\)])",
+    )
+    .unwrap();
+    let keyword_re = Regex::new(r"(?i)合集|全集|\bcomplete\b|\bbatch\b").unwrap();
+    let fansub_re = Regex::new(r"^(?<fansub>\[.*?\])?\s*(?<rest>.*)$").unwrap();
+    let bracket_re = Regex::new(r"[\[\(][^\]\)]*[\]\)]").unwrap();
+
+    let captures = fansub_re.captures(content)?;
+    let fansub = captures.name("fansub").map_or("", |m| m.as_str());
+    let rest = captures.name("rest").map_or("", |m| m.as_str());
+
+    let (batch, marker) = match range_re.captures(rest) {
+        Some(captures) => {
+            let first = captures.name("first")?;
+            let last = captures.name("last")?;
+            let range = EpisodeRange {
+                first: first.as_str().parse().ok()?,
+                last: Some(last.as_str().parse().ok()?),
+            };
+            if range.last <= Some(range.first) {
+                return None;
+            }
+            (range, first.start())
+        }
+        None => {
+            let m = keyword_re.find(rest)?;
+            (
+                EpisodeRange {
+                    first: 1,
+                    last: None,
+                },
+                m.start(),
+            )
+        }
+    };
+
+    let head = &rest[..marker];
+    let head = &head[..head.find(['[', '(']).unwrap_or(head.len())];
+    let head = head.trim_matches(|c: char| c.is_whitespace() || c == '-');
+    let mut groups: Vec<_> = bracket_re
+        .find_iter(rest)
+        .filter(|m| !m.range().contains(&marker))
+        .collect();
+    let title = if head.is_empty() {
+        // [fansub][title][01-12][media_info]
+        let group = groups.first().filter(|m| m.start() < marker)?;
+        let title = &group.as_str()[1..group.len() - 1];
+        groups.remove(0);
+        title
+    } else {
+        head
+    };
+
+    let SeasonMatch { title, season, .. } = detect_season(title);
+    let title = remove_redundant_brackets(&title);
+    if title.is_empty() {
+        return None;
+    }
+    let media_info = groups.iter().map(|m| m.as_str()).collect();
+
+    Some((fansub.to_string(), title, season, batch, media_info))
+}
+
 #[inline]
 fn remove_redundant_brackets(title: &str) -> String {
     let re = Regex::new(r"[\[][^\]]*[\]]").unwrap();
@@ -179,18 +303,26 @@ fn parse_fansub_title_season(content: &str) -> Option<(String, String, u64)> {
 
     if slices.len() >= 2 {
         let fansub = &format!("[{}]", slices[0]);
-        let (title, season) = parse_bangumi_title_and_season(slices[1]);
+        let SeasonMatch { title, season, .. } = detect_season(slices[1]);
         Some((fansub.to_string(), title, season))
     } else {
         None
     }
 }
 
-fn parse_episode_num_and_media_info(title: &str) -> Option<(u64, String)> {
+fn parse_episode_num_and_media_info(
+    title: &str,
+) -> Option<(u64, Option<String>, Option<u64>, String)> {
     let slices: Vec<&str> = title.split(&['[', ']', '-'][..]).collect();
+    let episode_re = Regex::new(
+        r"(?i)^(\d+(?:\.\d+)?|(?:SP|OVA|OAD|Extra|特别篇|总集篇)(?:\s?\d+)?)(?:v(\d+))?$",
+    )
+    .unwrap();
 
     for (i, s) in slices.iter().enumerate() {
-        if let Ok(episode) = s.parse::<u64>() {
+        if let Some(captures) = episode_re.captures(s) {
+            let (episode, special) = parse_episode(&captures[1])?;
+            let revision = captures.get(2).map(|m| m.as_str().parse::<u64>().unwrap());
             let media_info = &slices[i + 1..]
                 .iter()
                 .map(|s| {
@@ -202,7 +334,7 @@ fn parse_episode_num_and_media_info(title: &str) -> Option<(u64, String)> {
                 })
                 .collect::<Vec<String>>()
                 .concat();
-            return Some((episode, media_info.to_owned()));
+            return Some((episode, special, revision, media_info.to_owned()));
         }
     }
 
@@ -236,11 +368,15 @@ impl RssParser for MikanParser {
         match rss_xml {
             Ok(rss_xml) => {
                 let mut rss_items = Vec::new();
+                let mut unparsed = Vec::new();
 
                 let raw_title_content =
                     strip_mikan_prefix(rss_xml.channel.title.as_str()).to_string();
-                let (channel_title, channel_season) =
-                    parse_bangumi_title_and_season(&raw_title_content);
+                let SeasonMatch {
+                    title: channel_title,
+                    season: channel_season,
+                    confidence: channel_confidence,
+                } = detect_season(&raw_title_content);
 
                 if channel_title == "我的番组" {
                     debug!("[parser] Parsing aggregation items...");
@@ -252,22 +388,37 @@ impl RssParser for MikanParser {
                             if channel_title != "我的番组" {
                                 // PRIORITY: rss title > channel title > item title
                                 rss_item.title = channel_title.to_string();
-                                rss_item.season = channel_season;
+                                // Keep the season of the item if the channel doesn't tell one.
+                                if rss_item.special.is_none()
+                                    && channel_confidence > SeasonConfidence::Assumed
+                                {
+                                    rss_item.season = channel_season;
+                                }
 
                                 if let Some(rss_title) = &rss.title {
                                     rss_item.title = rss_title.to_string();
                                 }
-                                if let Some(rss_season) = rss.season {
+                                if let Some(rss_season) =
+                                    rss.season.filter(|_| rss_item.special.is_none())
+                                {
                                     rss_item.season = rss_season;
                                 }
                                 if let Some(category) = &rss.category {
                                     rss_item.category = category.to_string();
                                 }
                             }
+                            if rss.skip_episode_titles {
+                                rss_item.episode_title.clear();
+                            }
                             rss_items.push(rss_item);
                         }
                         Err(err) => {
                             error!("[parser] {}", err);
+                            unparsed.push(UnparsedItem {
+                                title: item.title.clone(),
+                                guid: item.link.clone(),
+                                error: err.to_string(),
+                            });
                         }
                     }
                 }
@@ -275,6 +426,7 @@ impl RssParser for MikanParser {
                 Ok(RssSubscription {
                     url: rss_xml.channel.link,
                     items: rss_items,
+                    unparsed,
                 })
             }
             Err(err) => Err(super::ParsingError::InvalidRss(err.to_string())),
@@ -338,7 +490,7 @@ mod tests {
     use crate::{
         downloader::{TorrentMeta, TorrentMetaBuilder},
         rss::{
-            parsers::{mikan::MikanParser, RssParser},
+            parsers::{mikan::MikanParser, parse_pub_date, RssParser},
             RssSubscription, RssSubscriptionItem,
         },
     };
@@ -363,78 +515,184 @@ mod tests {
             "[云歌字幕组&萌樱字幕组][4月新番][无名记忆 Unnamed Memory][01][HEVC][x265 10bit][1080p][简体中文][先行版]",
             "[喵萌奶茶屋&LoliHouse] 迷宫饭 / Dungeon Meshi / Delicious in Dungeon - 19v2 [WebRip 1080p HEVC-10bit AAC EAC3][简繁日内封字幕]",
             "[喵萌奶茶屋&LoliHouse] 物语系列 / Monogatari Series: Off & Monster Season - 01 愚物语 [WebRip 1080p HEVC-10bit AAC ASSx2][简繁内封字幕]",
+            "[云歌字幕组][无名记忆 Unnamed Memory][02v2][1080p]",
         ];
         let result = vec![
+            RssSubscriptionItem {
+                fansub: "[喵萌奶茶屋&amp;LoliHouse]".to_string(),
+                title: "葬送的芙莉莲".to_string(),
+                season: 1,
+                episode: 17,
+                media_info: "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[GJ.Y]".to_string(),
+                title: "欢迎来到实力至上主义的教室".to_string(),
+                season: 3,
+                episode: 3,
+                media_info: "(Baha 1920x1080 AVC AAC MP4)".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[LoliHouse]".to_string(),
+                title: "指尖相触，恋恋不舍".to_string(),
+                season: 1,
+                episode: 2,
+                media_info: "[WebRip 1080p HEVC-10bit AAC][简繁内封字幕]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[喵萌奶茶屋]".to_string(),
+                title: "单间，光照尚好，附带天使。".to_string(),
+                season: 1,
+                episode: 1,
+                media_info: "[1080p][简日双语][招募翻译时轴]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[钉铛字幕组]".to_string(),
+                title: "哆啦A梦新番".to_string(),
+                season: 1,
+                episode: 521,
+                media_info: "[2018.05.18][1080P][附最新的动画组的特效]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[清蓝字幕组]".to_string(),
+                title: "新哆啦A梦".to_string(),
+                season: 1,
+                episode: 437,
+                media_info: "[GB][720P]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[云歌字幕组&萌樱字幕组]".to_string(),
+                title: "无名记忆 Unnamed Memory".to_string(),
+                season: 1,
+                episode: 1,
+                media_info: "[HEVC][x265 10bit][1080p][简体中文][先行版]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[喵萌奶茶屋&LoliHouse]".to_string(),
+                title: "迷宫饭".to_string(),
+                season: 1,
+                episode: 19,
+                revision: Some(2),
+                media_info: "[WebRip 1080p HEVC-10bit AAC EAC3][简繁日内封字幕]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[喵萌奶茶屋&LoliHouse]".to_string(),
+                title: "物语系列".to_string(),
+                episode_title: "愚物语".to_string(),
+                season: 1,
+                episode: 1,
+                media_info: "[WebRip 1080p HEVC-10bit AAC ASSx2][简繁内封字幕]".to_string(),
+                ..Default::default()
+            },
+            RssSubscriptionItem {
+                fansub: "[云歌字幕组]".to_string(),
+                title: "无名记忆 Unnamed Memory".to_string(),
+                season: 1,
+                episode: 2,
+                revision: Some(2),
+                media_info: "[1080p]".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        for (title, expect) in titles.iter().zip(result.iter()) {
+            let r = parse_rss_item_info(title);
+            assert!(r.is_some(), "title: {}", title);
+            assert_eq!(r.unwrap(), *expect, "title: {}", title);
+        }
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let range = |first, last| EpisodeRange { first, last };
+        let cases = vec![
             (
-                "[喵萌奶茶屋&amp;LoliHouse]".to_string(),
-                "葬送的芙莉莲".to_string(),
-                1,
-                17,
-                "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]".to_string(),
-            ),
-            (
-                "[GJ.Y]".to_string(),
-                "欢迎来到实力至上主义的教室".to_string(),
-                3,
-                3,
-                "(Baha 1920x1080 AVC AAC MP4)".to_string(),
-            ),
-            (
-                "[LoliHouse]".to_string(),
-                "指尖相触，恋恋不舍".to_string(),
-                1,
-                2,
-                "[WebRip 1080p HEVC-10bit AAC][简繁内封字幕]".to_string(),
-            ),
-            (
-                "[喵萌奶茶屋]".to_string(),
-                "单间，光照尚好，附带天使。".to_string(),
-                1,
-                1,
-                "[1080p][简日双语][招募翻译时轴]".to_string(),
-            ),
-            (
-                "[钉铛字幕组]".to_string(),
-                "哆啦A梦新番".to_string(),
-                1,
-                521,
-                "[2018.05.18][1080P][附最新的动画组的特效]".to_string(),
+                "[LoliHouse] 迷宫饭 / Dungeon Meshi [01-24 合集][WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]",
+                Some(("[LoliHouse]", "迷宫饭", 1, range(1, Some(24)), "[WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]")),
             ),
             (
-                "[清蓝字幕组]".to_string(),
-                "新哆啦A梦".to_string(),
-                1,
-                437,
-                "[GB][720P]".to_string(),
+                "[Sakurato] Spy x Family - 01-13 Fin [1080p][CHS]",
+                Some(("[Sakurato]", "Spy x Family", 1, range(1, Some(13)), "[1080p][CHS]")),
             ),
             (
-                "[云歌字幕组&萌樱字幕组]".to_string(),
-                "无名记忆 Unnamed Memory".to_string(),
-                1,
-                1,
-                "[HEVC][x265 10bit][1080p][简体中文][先行版]".to_string(),
+                "【喵萌奶茶屋】★10月新番★[葬送的芙莉莲 / Sousou no Frieren][01-28][1080p][简日双语][合集]",
+                Some(("[喵萌奶茶屋]", "葬送的芙莉莲", 1, range(1, Some(28)), "[1080p][简日双语][合集]")),
             ),
             (
-                "[喵萌奶茶屋&LoliHouse]".to_string(),
-                "迷宫饭".to_string(),
-                1,
-                19,
-                "[WebRip 1080p HEVC-10bit AAC EAC3][简繁日内封字幕]".to_string(),
+                "[VCB-Studio] Yuru Camp / ゆるキャン△ [Ma10p_1080p][BDRip Complete]",
+                Some(("[VCB-Studio]", "Yuru Camp", 1, range(1, None), "[Ma10p_1080p]")),
             ),
+            ("[LoliHouse] 指尖相触，恋恋不舍 / Yubisaki to Renren - 02 [WebRip 1080p HEVC-10bit AAC]", None),
+            ("[钉铛字幕组]哆啦A梦新番|Doraemon[521][2018.05.18][1080P][附最新的动画组的特效]", None),
+            ("[GJ.Y] 欢迎来到实力至上主义的教室 第三季 - 03 (Baha 1920x1080 AVC AAC MP4)", None),
+        ];
+
+        for (title, expect) in cases {
+            let content = pretreat_rss_item_title(title.to_string());
+            let expect = expect.map(|(fansub, title, season, batch, media_info)| {
+                (fansub.to_string(), title.to_string(), season, batch, media_info.to_string())
+            });
+            assert_eq!(parse_batch(&content), expect, "title: {}", title);
+        }
+
+        let item = parse_rss_item_info("[Sakurato] Spy x Family - 01-13 Fin [1080p][CHS]").unwrap();
+        assert_eq!((item.episode, item.batch), (1, Some(range(1, Some(13)))));
+    }
+
+    #[test]
+    fn test_parse_episode_title() {
+        let cases = [
+            ("愚物语 ", "愚物语"),
+            ("「拷问」的时间", "「拷问」的时间"),
+            ("「冒险的终点」", "冒险的终点"),
+            ("The Journey's End (1080p)", "The Journey's End"),
+            ("（B-Global 1920x1080 HEVC AAC MKV）", ""),
+            ("END", ""),
+            ("完", ""),
+            ("", ""),
+        ];
+        for (content, expect) in cases {
+            assert_eq!(parse_episode_title(content), expect, "content: {}", content);
+        }
+
+        let item = parse_rss_item_info("[Sakurato] Spy x Family - 25 END [1080p][CHS]").unwrap();
+        assert_eq!((item.episode, item.episode_title.as_str()), (25, ""));
+    }
+
+    #[test]
+    fn test_parse_special() {
+        let cases = [
             (
-                "[喵萌奶茶屋&LoliHouse]".to_string(),
-                "物语系列".to_string(),
-                1,
-                1,
-                "[WebRip 1080p HEVC-10bit AAC ASSx2][简繁内封字幕]".to_string(),
+                "[LoliHouse] 药屋少女的呢喃 / Kusuriya no Hitorigoto - 13.5 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕]",
+                ("药屋少女的呢喃", 13, "13.5"),
             ),
+            ("[LoliHouse] 我推的孩子 / Oshi no Ko - OVA [WebRip 1080p HEVC-10bit AAC]", ("我推的孩子", 1, "OVA")),
+            ("[ANi] 葬送的芙莉莲 - SP02 [1080P][Baha][WEB-DL][AAC AVC][CHT]", ("葬送的芙莉莲", 2, "SP02")),
+            ("[ANi] 摇曳露营△ 第三季 - 00 [1080P][Baha][WEB-DL][AAC AVC][CHT]", ("摇曳露营△", 0, "00")),
+            ("[喵萌奶茶屋][单间，光照尚好，附带天使。][OAD][1080p]", ("单间，光照尚好，附带天使。", 1, "OAD")),
+            ("[桜都字幕组][无职转生][特别篇][1080p]", ("无职转生", 1, "特别篇")),
         ];
 
-        for (title, expect) in titles.iter().zip(result.iter()) {
-            let r = parse_rss_item_info(title);
-            assert!(r.is_some(), "title: {}", title);
-            assert_eq!(r.unwrap(), *expect, "title: {}", title);
+        for (title, (show_name, episode, special)) in cases {
+            let item = parse_rss_item_info(title).unwrap();
+            assert_eq!(item.title, show_name, "title: {}", title);
+            assert_eq!(item.season, 0, "title: {}", title);
+            assert_eq!(item.episode, episode, "title: {}", title);
+            assert_eq!(item.special.as_deref(), Some(special), "title: {}", title);
         }
+
+        // A title starting with `SP` isn't a special.
+        let item = parse_rss_item_info("[LoliHouse] Spy x Family - 01 [WebRip 1080p]").unwrap();
+        assert_eq!((item.season, item.episode, item.special), (1, 1, None));
+        assert_eq!(parse_episode("13.5v"), None);
     }
 
     #[test]
@@ -465,6 +723,8 @@ mod tests {
                     torrent: TorrentMeta::builder()
                         .url("https://mikanani.me/Download/20240118/059724511d60173251b378b04709aceff92fffb5.torrent".to_string())
                         .build(),
+                    pub_date: parse_pub_date("2024-01-18T06:57:43.93"),
+                    ..Default::default()
                 },
                 RssSubscriptionItem {
                     url: "https://mikanani.me/Home/Episode/872ab5abd72ea223d2a2e36688cc96f83bb71d42".to_string(),
@@ -478,8 +738,11 @@ mod tests {
                     torrent: TorrentMeta::builder()
                         .url("https://mikanani.me/Download/20240111/872ab5abd72ea223d2a2e36688cc96f83bb71d42.torrent".to_string())
                         .build(),
+                    pub_date: parse_pub_date("2024-01-11T06:57:59.057"),
+                    ..Default::default()
                 },
             ],
+            unparsed: vec![],
         };
         assert_eq!(res, expect);
     }
@@ -507,8 +770,11 @@ mod tests {
                     torrent: TorrentMeta::builder()
                         .url("https://mikanani.me/Download/20240306/65515bee0f9e64d00613e148afac9fbf26e13060.torrent".to_string())
                         .build(),
+                    pub_date: parse_pub_date("2024-03-06T21:41:22.281"),
+                    ..Default::default()
                 },
             ],
+            unparsed: vec![],
         };
         assert_eq!(res, expect);
     }
@@ -534,6 +800,8 @@ mod tests {
                     torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240313/38b3ab86bc9046f12edca2a2408ac1e7161a8c94.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-13T23:31:32.102"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/d2e587e0e10d77fcebdc4552d0725e43e2fa2fe6".to_string(),
@@ -547,6 +815,8 @@ mod tests {
                     torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240313/d2e587e0e10d77fcebdc4552d0725e43e2fa2fe6.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-13T23:02:04.724"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/ef56a70e19199829a0280cc022ece291fa186316".to_string(),
@@ -560,6 +830,8 @@ mod tests {
                     torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240313/ef56a70e19199829a0280cc022ece291fa186316.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-13T22:01:57.497"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/49b9c8dd833629d39e09a4e9568bde6b6a71a01b".to_string(),
@@ -573,6 +845,8 @@ mod tests {
                 torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240313/49b9c8dd833629d39e09a4e9568bde6b6a71a01b.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-13T20:31:07.116"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/f6d8f1b7131135c2c8b295aca18c64cb6405e2aa".to_string(),
@@ -586,6 +860,8 @@ mod tests {
                 torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240312/f6d8f1b7131135c2c8b295aca18c64cb6405e2aa.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-12T00:31:33.72"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/da075c8a8e0b9f71e130b978fb94e4def0745b30".to_string(),
@@ -599,6 +875,8 @@ mod tests {
                 torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240310/da075c8a8e0b9f71e130b978fb94e4def0745b30.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-10T20:46:52.314"),
+                ..Default::default()
             },
             RssSubscriptionItem {
                 url: "https://mikanani.me/Home/Episode/6f9bb9e56663194eb68a0811890751d1e66f6fbd".to_string(),
@@ -612,6 +890,8 @@ mod tests {
                 torrent: TorrentMeta::builder()
                     .url("https://mikanani.me/Download/20240310/6f9bb9e56663194eb68a0811890751d1e66f6fbd.torrent".to_string())
                     .build(),
+                pub_date: parse_pub_date("2024-03-10T01:31:34.279"),
+                ..Default::default()
             },
         ];
         res.items.iter().zip(expect.iter()).for_each(|(a, b)| {
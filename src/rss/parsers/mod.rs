@@ -1,8 +1,24 @@
-mod mikan;
+mod acgrip;
+mod dmhy;
+mod generic;
+pub(crate) mod mikan;
+mod nyaa;
+mod season;
+pub mod torznab;
 
-use super::{Rss, RssSubscription, RssType};
+use super::{HttpCache, Rss, RssSubscription, RssSubscriptionItem, RssType};
+use crate::downloader::TorrentMeta;
+use crate::http;
+pub use acgrip::AcgRipParser;
 use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+pub use dmhy::DmhyParser;
+pub use generic::GenericParser;
 pub use mikan::MikanParser;
+pub use nyaa::NyaaParser;
+use regex::Regex;
+use reqwest::{header, StatusCode};
+pub use torznab::TorznabParser;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ParsingError {
@@ -16,26 +32,79 @@ pub enum ParsingError {
     UnrecognizedEpisode(String),
 }
 
+/// Download the feed, sending the validators of the cache if any.
+///
+/// Returns `None` if the server answers `304 Not Modified`.
+async fn fetch(
+    rss: &Rss,
+    http_cache: &HttpCache,
+) -> Result<Option<(String, HttpCache)>, ParsingError> {
+    let client = http::client();
+
+    let mut request = client.get(&rss.url);
+    if let Some(etag) = &http_cache.etag {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &http_cache.last_modified {
+        request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+    }
+
+    let response = client
+        .send(request)
+        .await
+        .map_err(|err| ParsingError::DownloadFailed(rss.url.clone(), err.to_string()))?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+
+    let header_value = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value: &header::HeaderValue| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let http_cache = HttpCache {
+        etag: header_value(header::ETAG),
+        last_modified: header_value(header::LAST_MODIFIED),
+    };
+
+    let content = response
+        .text()
+        .await
+        .map_err(|err| ParsingError::DownloadFailed(rss.url.clone(), err.to_string()))?;
+    Ok(Some((content, http_cache)))
+}
+
 #[async_trait]
 #[allow(async_fn_in_trait)]
 pub trait RssParser: Send + Sync {
     fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError>;
 
     async fn parse(&self, rss: &Rss) -> Result<RssSubscription, ParsingError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .unwrap();
-        let content = match client.get(&rss.url).send().await {
-            Ok(response) => match response.text().await {
-                Ok(content) => content,
-                Err(err) => {
-                    return Err(ParsingError::DownloadFailed(rss.url.clone(), err.to_string()))
-                }
-            },
-            Err(err) => return Err(ParsingError::DownloadFailed(rss.url.clone(), err.to_string())),
-        };
-        self.parse_content(&rss, &content)
+        match fetch(rss, &HttpCache::default()).await? {
+            Some((content, _)) => self.parse_content(rss, &content),
+            None => Err(ParsingError::DownloadFailed(
+                rss.url.clone(),
+                "Unexpected 304 Not Modified".to_string(),
+            )),
+        }
+    }
+
+    /// Parse the feed only if it has changed since the validators in `rss.http_cache`.
+    ///
+    /// Returns `None` if the feed is not modified, otherwise the subscription and
+    /// the validators to send next time.
+    async fn parse_if_modified(
+        &self,
+        rss: &Rss,
+    ) -> Result<Option<(RssSubscription, HttpCache)>, ParsingError> {
+        match fetch(rss, &rss.http_cache).await? {
+            Some((content, http_cache)) => {
+                Ok(Some((self.parse_content(rss, &content)?, http_cache)))
+            }
+            None => Ok(None),
+        }
     }
 }
 
@@ -43,8 +112,231 @@ pub async fn parse(rss: &Rss) -> Result<RssSubscription, ParsingError> {
     get_parser(&rss.rss_type).parse(rss).await
 }
 
+pub async fn parse_if_modified(
+    rss: &Rss,
+) -> Result<Option<(RssSubscription, HttpCache)>, ParsingError> {
+    get_parser(&rss.rss_type).parse_if_modified(rss).await
+}
+
 pub fn get_parser(rss_type: &RssType) -> Box<dyn RssParser> {
     match rss_type {
         RssType::Mikan => Box::new(MikanParser::new()),
+        RssType::Generic => Box::new(GenericParser::new()),
+        RssType::Nyaa => Box::new(NyaaParser::new()),
+        RssType::Dmhy => Box::new(DmhyParser::new()),
+        RssType::AcgRip => Box::new(AcgRipParser::new()),
+        RssType::Torznab => Box::new(TorznabParser::new()),
+    }
+}
+
+/// Build a subscription item from a release title.
+///
+/// The title is parsed with the same heuristics as mikan, and the title, season and
+/// category configured on the rss override the parsed ones. The episode title is
+/// dropped if the rss skips them.
+fn build_rss_item(
+    rss: &Rss,
+    url: &str,
+    title: &str,
+    torrent: TorrentMeta,
+) -> Result<RssSubscriptionItem, ParsingError> {
+    let info = mikan::parse_rss_item_info(title).ok_or_else(|| {
+        ParsingError::UnrecognizedEpisode(format!("Failed to parse rss item: {}", title))
+    })?;
+
+    let mut rss_item = RssSubscriptionItem {
+        url: url.to_string(),
+        torrent,
+        ..info
+    };
+
+    // PRIORITY: rss title > item title
+    if let Some(rss_title) = &rss.title {
+        rss_item.title = rss_title.to_string();
+    }
+    // Specials stay in season 0.
+    if let Some(rss_season) = rss.season.filter(|_| rss_item.special.is_none()) {
+        rss_item.season = rss_season;
+    }
+    if let Some(category) = &rss.category {
+        rss_item.category = category.to_string();
+    }
+    if rss.skip_episode_titles {
+        rss_item.episode_title.clear();
+    }
+
+    Ok(rss_item)
+}
+
+/// Single file releases often use the file name as the title,
+/// strip the extension so the media info can be recognized.
+pub(crate) fn strip_file_extension(title: &str) -> String {
+    let re = Regex::new(r"(?i)\.(mkv|mp4|avi|ts)$").unwrap();
+    re.replace(title.trim(), "").to_string()
+}
+
+/// Parse a human readable size like `1.4 GiB` or `634.12 MB` into bytes.
+///
+/// Binary units (`KiB`, `MiB`, ...) are powers of 1024, decimal units (`KB`, `MB`, ...)
+/// are powers of 1000.
+pub(crate) fn parse_size(content: &str) -> Option<u64> {
+    let content = content.trim();
+    let unit_start = content
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(content.len());
+    let value = content[..unit_start].parse::<f64>().ok()?;
+
+    let multiplier: u64 = match content[unit_start..].trim().to_lowercase().as_str() {
+        "" | "b" | "byte" | "bytes" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000_u64.pow(2),
+        "g" | "gb" => 1000_u64.pow(3),
+        "t" | "tb" => 1000_u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+
+    Some((value * multiplier as f64).round() as u64)
+}
+
+/// Parse the publication date of an item, in RFC 2822 (RSS), RFC 3339 (Atom),
+/// or the naive `2024-03-13T23:31:32.102` of mikan, which is in China Standard Time.
+pub(crate) fn parse_pub_date(content: &str) -> Option<DateTime<Local>> {
+    let content = content.trim();
+    if let Ok(date) = DateTime::parse_from_rfc2822(content) {
+        return Some(date.with_timezone(&Local));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(content) {
+        return Some(date.with_timezone(&Local));
+    }
+
+    let date = NaiveDateTime::parse_from_str(content, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    let china_standard_time = FixedOffset::east_opt(8 * 3600).unwrap();
+    date.and_local_timezone(china_standard_time)
+        .single()
+        .map(|date| date.with_timezone(&Local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1.4 GiB"), Some(1503238554));
+        assert_eq!(parse_size("634.12 MB"), Some(634120000));
+        assert_eq!(parse_size("512KiB"), Some(524288));
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("12 Bytes"), Some(12));
+        assert_eq!(parse_size("1.4 parsecs"), None);
+        assert_eq!(parse_size("GiB"), None);
+    }
+
+    #[test]
+    fn test_build_rss_item_season() {
+        let rss = Rss::builder()
+            .url("https://example.com/rss".to_string())
+            .rss_type(RssType::Generic)
+            .season(Some(2))
+            .build();
+        let build = |title| {
+            let torrent = TorrentMeta::builder().url("".to_string()).build();
+            build_rss_item(&rss, "", title, torrent).unwrap()
+        };
+
+        assert_eq!(build("[LoliHouse] Oshi no Ko - 05 [WebRip 1080p]").season, 2);
+        // Specials stay in season 0.
+        let item = build("[LoliHouse] Oshi no Ko - OVA [WebRip 1080p]");
+        assert_eq!((item.season, item.special.as_deref()), (0, Some("OVA")));
+    }
+
+    #[test]
+    fn test_build_rss_item_episode_title() {
+        let mut rss = Rss::builder()
+            .url("https://example.com/rss".to_string())
+            .rss_type(RssType::Generic)
+            .build();
+        let title = "[LoliHouse] 物语系列 / Monogatari Series - 01 愚物语 [WebRip 1080p]";
+        let build = |rss: &Rss| {
+            let torrent = TorrentMeta::builder().url("".to_string()).build();
+            build_rss_item(rss, "", title, torrent).unwrap()
+        };
+
+        assert_eq!(build(&rss).episode_title, "愚物语");
+        rss.skip_episode_titles = true;
+        assert_eq!(build(&rss).episode_title, "");
+    }
+
+    #[test]
+    fn test_parse_pub_date() {
+        let expect = DateTime::parse_from_rfc3339("2024-01-19T16:01:55Z").unwrap();
+        assert_eq!(parse_pub_date("Fri, 19 Jan 2024 16:01:55 -0000"), Some(expect.into()));
+        assert_eq!(parse_pub_date(" 2024-01-19T16:01:55+00:00 "), Some(expect.into()));
+        assert_eq!(parse_pub_date("2024-01-20T00:01:55"), Some(expect.into()));
+        assert_eq!(
+            parse_pub_date("2024-01-20T00:01:55.279"),
+            Some((expect + chrono::Duration::milliseconds(279)).into())
+        );
+        assert_eq!(parse_pub_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_strip_file_extension() {
+        assert_eq!(
+            strip_file_extension("[SubsPlease] Sousou no Frieren - 18 (1080p) [C3C9B8A4].mkv"),
+            "[SubsPlease] Sousou no Frieren - 18 (1080p) [C3C9B8A4]"
+        );
+        assert_eq!(
+            strip_file_extension("[Erai-raws] Sousou no Frieren - 18 [720p]"),
+            "[Erai-raws] Sousou no Frieren - 18 [720p]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_if_modified() {
+        use crate::test::{serve_http, StubResponse};
+
+        let content = std::fs::read_to_string("./tests/dataset/generic-1.rss").unwrap();
+        let (base_url, requests) = serve_http(vec![
+            StubResponse {
+                status: 200,
+                headers: vec![
+                    ("ETag".to_string(), "\"5f3c\"".to_string()),
+                    ("Last-Modified".to_string(), "Fri, 19 Jan 2024 16:01:55 GMT".to_string()),
+                ],
+                body: content,
+            },
+            StubResponse {
+                status: 304,
+                headers: vec![],
+                body: "".to_string(),
+            },
+        ])
+        .await;
+
+        let mut rss = Rss::builder()
+            .url(format!("{}/feed.xml", base_url))
+            .rss_type(RssType::Generic)
+            .build();
+
+        let (feeds, http_cache) = parse_if_modified(&rss).await.unwrap().unwrap();
+        assert_eq!(feeds.items.len(), 3);
+        assert_eq!(http_cache.etag.as_deref(), Some("\"5f3c\""));
+        assert_eq!(http_cache.last_modified.as_deref(), Some("Fri, 19 Jan 2024 16:01:55 GMT"));
+
+        rss.http_cache = http_cache;
+        assert!(parse_if_modified(&rss).await.unwrap().is_none());
+
+        let requests = requests.await.unwrap();
+        assert!(!requests[0].to_lowercase().contains("if-none-match"));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("if-none-match: \"5f3c\""));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("if-modified-since: fri, 19 jan 2024 16:01:55 gmt"));
     }
 }
@@ -0,0 +1,165 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::{
+    build_rss_item, parse_pub_date, parse_size, strip_file_extension, ParsingError, RssParser,
+};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
+
+fn parse_rss_item(rss: &Rss, item: &NyaaRssItem) -> Result<RssSubscriptionItem, ParsingError> {
+    let torrent = TorrentMeta::builder()
+        .url(item.link.trim().to_string())
+        .hash(
+            item.info_hash
+                .as_deref()
+                .map(|hash| hash.trim().to_lowercase())
+                .filter(|hash| !hash.is_empty()),
+        )
+        .build();
+
+    let url = item.guid.as_deref().unwrap_or(&item.link).trim();
+    let mut rss_item = build_rss_item(rss, url, &strip_file_extension(&item.title), torrent)?;
+    rss_item.size = item.size.as_deref().and_then(parse_size);
+    rss_item.seeders = item.seeders;
+    rss_item.pub_date = item.pub_date.as_deref().and_then(parse_pub_date);
+    rss_item.trusted = item
+        .trusted
+        .as_deref()
+        .map(|trusted| trusted.trim() == "Yes");
+    if let Some(category) = &item.category {
+        rss_item.feed_category = category.trim().to_string();
+    }
+
+    Ok(rss_item)
+}
+
+/// The parser for nyaa.si feeds.
+///
+/// Besides the standard rss fields, it reads the `nyaa:` namespace to get the
+/// info hash, size, seeders, category and trusted flag of each torrent without
+/// downloading it.
+#[derive(Default)]
+pub struct NyaaParser {}
+
+impl NyaaParser {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl RssParser for NyaaParser {
+    fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+        let rss_xml: NyaaRss =
+            serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+        let mut rss_items = Vec::new();
+        let mut unparsed = Vec::new();
+        for item in rss_xml.channel.item {
+            match parse_rss_item(rss, &item) {
+                Ok(rss_item) => rss_items.push(rss_item),
+                Err(err) => {
+                    error!("[parser] {}", err);
+                    unparsed.push(UnparsedItem {
+                        title: item.title.clone(),
+                        guid: item
+                            .guid
+                            .as_deref()
+                            .unwrap_or(&item.link)
+                            .trim()
+                            .to_string(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(RssSubscription {
+            url: rss.url.clone(),
+            items: rss_items,
+            unparsed,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NyaaRss {
+    channel: NyaaRssChannel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NyaaRssChannel {
+    #[serde(default)]
+    item: Vec<NyaaRssItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NyaaRssItem {
+    title: String,
+    link: String,
+    #[serde(default)]
+    guid: Option<String>,
+    #[serde(default)]
+    seeders: Option<u64>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+    #[serde(rename = "infoHash", default)]
+    info_hash: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    size: Option<String>,
+    #[serde(default)]
+    trusted: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::RssType;
+    use std::fs::read_to_string;
+
+    #[tokio::test]
+    async fn test_parse_rss_content() {
+        let rss_content = read_to_string("./tests/dataset/nyaa-1.rss").unwrap();
+
+        let rss = Rss::builder()
+            .url("https://nyaa.si/?page=rss&q=Frieren".to_string())
+            .rss_type(RssType::Nyaa)
+            .build();
+        let parser = NyaaParser::new();
+        let res = parser.parse_content(&rss, &rss_content).unwrap();
+
+        assert_eq!(res.url, "https://nyaa.si/?page=rss&q=Frieren");
+        assert_eq!(res.items.len(), 2);
+
+        let item = &res.items[0];
+        assert_eq!(item.url, "https://nyaa.si/view/1771374");
+        assert_eq!(item.title, "Sousou no Frieren");
+        assert_eq!(item.season, 1);
+        assert_eq!(item.episode, 18);
+        assert_eq!(item.fansub, "[SubsPlease]");
+        assert_eq!(item.media_info, "(1080p) [C3C9B8A4]");
+        assert_eq!(item.size, Some(1503238554));
+        assert_eq!(item.pub_date, parse_pub_date("2024-01-19T16:01:55Z"));
+        assert_eq!(item.seeders, Some(1874));
+        assert_eq!(item.feed_category, "Anime - English-translated");
+        // The site category doesn't become the category of the downloads.
+        assert_eq!(item.category, "");
+        assert_eq!(item.trusted, Some(true));
+        assert_eq!(item.torrent.url, "https://nyaa.si/download/1771374.torrent");
+        // The info hash comes from the feed, no need to download the torrent.
+        assert_eq!(
+            item.torrent.get_torrent_id().await.unwrap(),
+            "7f2b35c2a8e5b4a4d6f1e2c3b4a5968778695a4b"
+        );
+
+        let item = &res.items[1];
+        assert_eq!(item.fansub, "[Erai-raws]");
+        assert_eq!(item.episode, 18);
+        assert_eq!(item.size, Some(769130496));
+        assert_eq!(item.seeders, Some(215));
+        assert_eq!(item.trusted, Some(false));
+        assert_eq!(item.torrent.hash.as_deref(), Some("0a1b2c3d4e5f60718293a4b5c6d7e8f901234567"));
+    }
+}
@@ -0,0 +1,296 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+/// How sure the season detected in a show title is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SeasonConfidence {
+    /// No season in the title, the season 1 is assumed
+    Assumed,
+    /// Implied by a part or a roman numeral, e.g. `Part 2` or `Overlord IV`
+    Low,
+    /// Abbreviated, e.g. `S2` or `无职转生Ⅱ`
+    Medium,
+    /// Written out, e.g. `第二季`, `Season 2` or `2nd Season`
+    High,
+}
+
+/// The season detected in a show title, see [`detect_season`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeasonMatch {
+    /// The first title without the seasons, e.g. `葬送的芙莉莲` of
+    /// `葬送的芙莉莲 第二季 / Sousou no Frieren 2nd Season`
+    pub title: String,
+    pub season: u64,
+    pub confidence: SeasonConfidence,
+}
+
+type SeasonParser = fn(&Captures) -> Option<u64>;
+
+static SEASON_RES: Lazy<Vec<(Regex, SeasonConfidence, SeasonParser)>> = Lazy::new(|| {
+    let number: SeasonParser = |captures| captures[1].parse().ok();
+    let chinese_number: SeasonParser = |captures| parse_chinese_number(&captures[1]);
+    vec![
+        // 第二季, 第十一季, 第2期
+        (
+            Regex::new(r"\s*第\s*([0-9零〇一二两三四五六七八九十]+)\s*[季期]").unwrap(),
+            SeasonConfidence::High,
+            chinese_number,
+        ),
+        // Season 2
+        (Regex::new(r"(?i)\s*\bSeason\s*(\d{1,2})\b").unwrap(), SeasonConfidence::High, number),
+        // 2nd Season, 2nd STAGE
+        (
+            Regex::new(r"(?i)\s*\b(\d{1,2})(?:st|nd|rd|th)\s+(?:Season|Stage)\b").unwrap(),
+            SeasonConfidence::High,
+            number,
+        ),
+        // Second Season
+        (
+            Regex::new(
+                r"(?i)\s*\b(first|second|third|fourth|fifth|sixth|seventh|eighth|ninth|tenth)\s+Season\b",
+            )
+            .unwrap(),
+            SeasonConfidence::High,
+            |captures| parse_ordinal(&captures[1]),
+        ),
+        // S2, S02
+        (Regex::new(r"\s*\bS(\d{1,2})\b").unwrap(), SeasonConfidence::Medium, number),
+        // 无职转生Ⅱ
+        (
+            Regex::new(r"\s*([ⅡⅢⅣⅤⅥⅦⅧⅨⅩ])").unwrap(),
+            SeasonConfidence::Medium,
+            |captures| parse_roman_numeral(&captures[1]),
+        ),
+        // Part 2
+        (Regex::new(r"(?i)\s*\bPart\s*(\d{1,2})\b").unwrap(), SeasonConfidence::Low, number),
+        // 第二部分
+        (
+            Regex::new(r"\s*第\s*([0-9零〇一二两三四五六七八九十]+)\s*部分").unwrap(),
+            SeasonConfidence::Low,
+            chinese_number,
+        ),
+        // Overlord IV, `V` and `X` are left out as they are often a part of the name.
+        (
+            Regex::new(r"\s*\b(II|III|IV|VI|VII|VIII|IX)\b").unwrap(),
+            SeasonConfidence::Low,
+            |captures| parse_roman_numeral(&captures[1]),
+        ),
+    ]
+});
+
+/// Detect the season in show titles separated by `/` or `|`, e.g.
+/// `欢迎来到实力至上主义的教室 第三季 / Youkoso Jitsuryoku Shijou Shugi no Kyoushitsu e 3rd Season`.
+///
+/// Every season marker is removed from the title, and the most confident one of all
+/// the titles is the season, e.g. `第三季` rather than `Part 2` in `第三季 Part 2`.
+pub fn detect_season(content: &str) -> SeasonMatch {
+    let mut titles = content
+        .split(['/', '|'])
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(detect_title_season);
+
+    let Some(mut result) = titles.next() else {
+        return SeasonMatch {
+            title: content.trim().to_string(),
+            season: 1,
+            confidence: SeasonConfidence::Assumed,
+        };
+    };
+    for other in titles {
+        if other.confidence > result.confidence {
+            result.season = other.season;
+            result.confidence = other.confidence;
+        }
+    }
+    result
+}
+
+fn detect_title_season(title: &str) -> SeasonMatch {
+    let mut result = SeasonMatch {
+        title: title.to_string(),
+        season: 1,
+        confidence: SeasonConfidence::Assumed,
+    };
+
+    for (re, confidence, parse) in SEASON_RES.iter() {
+        let Some(captures) = re.captures(&result.title) else {
+            continue;
+        };
+        // An implied season is only at the end of the title, or before a subtitle,
+        // e.g. `Mushoku Tensei II: Isekai Ittara Honki Dasu`.
+        let whole = captures.get(0).unwrap();
+        let rest = &result.title[whole.end()..];
+        if *confidence == SeasonConfidence::Low
+            && rest
+                .trim_start()
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let Some(season) = parse(&captures) else {
+            continue;
+        };
+
+        let title = format!("{}{}", &result.title[..whole.start()], rest);
+        let title = title.trim();
+        if title.is_empty() {
+            continue;
+        }
+        if *confidence > result.confidence {
+            result.season = season;
+            result.confidence = *confidence;
+        }
+        result.title = title.to_string();
+    }
+
+    result
+}
+
+/// Parse a number like `2`, `十一` or `二十`.
+fn parse_chinese_number(content: &str) -> Option<u64> {
+    if let Ok(number) = content.parse() {
+        return Some(number);
+    }
+
+    let (mut total, mut current) = (0, None);
+    for c in content.chars() {
+        let digit = match c {
+            '零' | '〇' => 0,
+            '一' => 1,
+            '二' | '两' => 2,
+            '三' => 3,
+            '四' => 4,
+            '五' => 5,
+            '六' => 6,
+            '七' => 7,
+            '八' => 8,
+            '九' => 9,
+            '十' => {
+                total += current.take().unwrap_or(1) * 10;
+                continue;
+            }
+            _ => return None,
+        };
+        if current.is_some() {
+            return None;
+        }
+        current = Some(digit);
+    }
+    Some(total + current.unwrap_or(0)).filter(|&number| number > 0)
+}
+
+fn parse_ordinal(content: &str) -> Option<u64> {
+    let ordinals = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+    let content = content.to_lowercase();
+    let index = ordinals.iter().position(|ordinal| *ordinal == content)?;
+    Some(index as u64 + 1)
+}
+
+fn parse_roman_numeral(content: &str) -> Option<u64> {
+    let numerals = [
+        ("II", 'Ⅱ'),
+        ("III", 'Ⅲ'),
+        ("IV", 'Ⅳ'),
+        ("V", 'Ⅴ'),
+        ("VI", 'Ⅵ'),
+        ("VII", 'Ⅶ'),
+        ("VIII", 'Ⅷ'),
+        ("IX", 'Ⅸ'),
+        ("X", 'Ⅹ'),
+    ];
+    let index = numerals.iter().position(|(ascii, unicode)| {
+        *ascii == content || content.chars().eq(std::iter::once(*unicode))
+    })?;
+    Some(index as u64 + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SeasonConfidence::*;
+
+    #[test]
+    fn test_detect_season() {
+        let cases = [
+            // mikan
+            ("葬送的芙莉莲 / Sousou no Frieren", "葬送的芙莉莲", 1, Assumed),
+            (
+                "欢迎来到实力至上主义的教室 第三季 / Youkoso Jitsuryoku Shijou Shugi no Kyoushitsu e 3rd Season",
+                "欢迎来到实力至上主义的教室",
+                3,
+                High,
+            ),
+            (
+                "为美好的世界献上祝福！第三季 / Kono Subarashii Sekai ni Shukufuku wo! 3",
+                "为美好的世界献上祝福！",
+                3,
+                High,
+            ),
+            ("间谍过家家 第二季 / SPY×FAMILY Season 2", "间谍过家家", 2, High),
+            ("擅长捉弄的高木同学 第三季", "擅长捉弄的高木同学", 3, High),
+            ("弱角友崎同学 2nd STAGE / Jaku-Chara Tomozaki-kun 2nd Stage", "弱角友崎同学", 2, High),
+            ("无职转生Ⅱ ～到了异世界就拿出真本事～", "无职转生 ～到了异世界就拿出真本事～", 2, Medium),
+            ("无职转生 第二季 Part 2 / Mushoku Tensei S2 Part 2", "无职转生", 2, High),
+            ("进击的巨人 最终季 第二部分 / Shingeki no Kyojin The Final Season Part 2", "进击的巨人 最终季", 2, Low),
+            ("物语系列 / Monogatari Series: Off & Monster Season", "物语系列", 1, Assumed),
+            ("单间，光照尚好，附带天使。", "单间，光照尚好，附带天使。", 1, Assumed),
+            ("我推的孩子 第二季", "我推的孩子", 2, High),
+            ("魔法科高校的劣等生 第三季", "魔法科高校的劣等生", 3, High),
+            ("名侦探柯南 第十一季", "名侦探柯南", 11, High),
+            ("哆啦A梦 第二十季", "哆啦A梦", 20, High),
+            ("海贼王 第二十一季", "海贼王", 21, High),
+            ("关于我转生变成史莱姆这档事 第3期", "关于我转生变成史莱姆这档事", 3, High),
+            ("摇曳露营△ 第2季", "摇曳露营△", 2, High),
+            // dmhy
+            ("Oshi no Ko 2nd Season", "Oshi no Ko", 2, High),
+            ("Kage no Jitsuryokusha ni Naritakute! 2nd season", "Kage no Jitsuryokusha ni Naritakute!", 2, High),
+            ("Re:Zero kara Hajimeru Isekai Seikatsu 3rd Season", "Re:Zero kara Hajimeru Isekai Seikatsu", 3, High),
+            ("Boku no Hero Academia Season 7", "Boku no Hero Academia", 7, High),
+            ("Mahoutsukai no Yome Season 2 Part 2", "Mahoutsukai no Yome", 2, High),
+            ("Shin no Nakama Second Season", "Shin no Nakama", 2, High),
+            ("Spy x Family Part 2", "Spy x Family", 2, Low),
+            ("Youjo Senki II", "Youjo Senki", 2, Low),
+            ("Mushoku Tensei II: Isekai Ittara Honki Dasu", "Mushoku Tensei: Isekai Ittara Honki Dasu", 2, Low),
+            // nyaa
+            ("Yuru Camp S3", "Yuru Camp", 3, Medium),
+            ("The Eminence in Shadow S02", "The Eminence in Shadow", 2, Medium),
+            ("Overlord IV", "Overlord", 4, Low),
+            ("Mob Psycho 100 III", "Mob Psycho 100", 3, Low),
+            ("Sword Art Online Alternative: Gun Gale Online II", "Sword Art Online Alternative: Gun Gale Online", 2, Low),
+            ("Shingeki no Kyojin: The Final Season", "Shingeki no Kyojin: The Final Season", 1, Assumed),
+            ("Kaiju No. 8", "Kaiju No. 8", 1, Assumed),
+            ("86 -Eighty Six-", "86 -Eighty Six-", 1, Assumed),
+            ("Blue Lock vs. U-20 Japan", "Blue Lock vs. U-20 Japan", 1, Assumed),
+            ("Mobile Suit Gundam X", "Mobile Suit Gundam X", 1, Assumed),
+            ("Final Fantasy VII Advent Children", "Final Fantasy VII Advent Children", 1, Assumed),
+            ("Dr. Stone: New World", "Dr. Stone: New World", 1, Assumed),
+            ("Season 2", "Season 2", 1, Assumed),
+        ];
+
+        for (content, title, season, confidence) in cases {
+            let expect = SeasonMatch {
+                title: title.to_string(),
+                season,
+                confidence,
+            };
+            assert_eq!(detect_season(content), expect, "content: {}", content);
+        }
+    }
+
+    #[test]
+    fn test_parse_chinese_number() {
+        assert_eq!(parse_chinese_number("2"), Some(2));
+        assert_eq!(parse_chinese_number("十"), Some(10));
+        assert_eq!(parse_chinese_number("十一"), Some(11));
+        assert_eq!(parse_chinese_number("二十"), Some(20));
+        assert_eq!(parse_chinese_number("两"), Some(2));
+        assert_eq!(parse_chinese_number("一二"), None);
+        assert_eq!(parse_chinese_number("零"), None);
+    }
+}
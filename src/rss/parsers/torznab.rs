@@ -0,0 +1,307 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::TorrentMeta;
+use crate::rss::parsers::{
+    build_rss_item, parse_pub_date, strip_file_extension, ParsingError, RssParser,
+};
+use crate::rss::{Rss, RssSubscription, RssSubscriptionItem, UnparsedItem};
+
+fn parse_rss_item(rss: &Rss, item: &TorznabItem) -> Result<RssSubscriptionItem, ParsingError> {
+    let non_empty = |url: &str| {
+        Some(url.trim())
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+    };
+    // PRIORITY: enclosure > link > magneturl attribute
+    let torrent_url = item
+        .enclosure
+        .as_ref()
+        .and_then(|enclosure| non_empty(&enclosure.url))
+        .or_else(|| item.link.as_deref().and_then(non_empty))
+        .or_else(|| item.attr("magneturl").map(str::to_string))
+        .ok_or_else(|| {
+            ParsingError::InvalidRss(format!("No torrent found in rss item: {}", item.title))
+        })?;
+
+    let torrent = TorrentMeta::builder()
+        .url(torrent_url.clone())
+        .hash(item.attr("infohash").map(str::to_lowercase))
+        .build();
+
+    let url = item.guid.as_deref().map(str::trim).unwrap_or(&torrent_url);
+    let mut rss_item = build_rss_item(rss, url, &strip_file_extension(&item.title), torrent)?;
+    rss_item.size = item
+        .attr("size")
+        .and_then(|size| size.parse().ok())
+        .or(item.size);
+    rss_item.seeders = item
+        .attr("seeders")
+        .and_then(|seeders| seeders.parse().ok());
+    rss_item.pub_date = item.pub_date.as_deref().and_then(parse_pub_date);
+    rss_item.feed_category = item.attrs("category").join(",");
+
+    Ok(rss_item)
+}
+
+/// Build the search url of a torznab indexer, e.g. a Jackett or Prowlarr endpoint
+/// like `http://127.0.0.1:9117/api/v2.0/indexers/all/results/torznab/api?apikey=xxx`.
+///
+/// The `t` and `q` parameters of the indexer url are replaced, others are kept.
+pub fn search_url(indexer_url: &str, query: &str) -> Result<String, ParsingError> {
+    let mut url = reqwest::Url::parse(indexer_url).map_err(|e| {
+        ParsingError::InvalidRss(format!("Invalid indexer url {}: {}", indexer_url, e))
+    })?;
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "t" && key != "q")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("t", "search")
+        .append_pair("q", query);
+
+    Ok(url.to_string())
+}
+
+/// Search the torznab indexer configured as the rss url.
+pub async fn search(rss: &Rss, query: &str) -> Result<RssSubscription, ParsingError> {
+    let mut rss = rss.clone();
+    rss.url = search_url(&rss.url, query)?;
+    TorznabParser::new().parse(&rss).await
+}
+
+/// The parser for torznab feeds served by indexers like Jackett or Prowlarr.
+///
+/// The size, seeders, info hash and categories are read from the `torznab:attr`
+/// elements, so the torrent doesn't need to be downloaded to be identified. The
+/// categories are only used by the filters as `feed_category`.
+#[derive(Default)]
+pub struct TorznabParser {}
+
+impl TorznabParser {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl RssParser for TorznabParser {
+    fn parse_content(&self, rss: &Rss, content: &str) -> Result<RssSubscription, ParsingError> {
+        let rss_xml: TorznabRss =
+            serde_xml_rs::from_str(content).map_err(|e| ParsingError::InvalidRss(e.to_string()))?;
+
+        let mut rss_items = Vec::new();
+        let mut unparsed = Vec::new();
+        for item in rss_xml.channel.item {
+            match parse_rss_item(rss, &item) {
+                Ok(rss_item) => rss_items.push(rss_item),
+                Err(err) => {
+                    error!("[parser] {}", err);
+                    unparsed.push(UnparsedItem {
+                        title: item.title.clone(),
+                        guid: item
+                            .guid
+                            .as_deref()
+                            .or(item
+                                .enclosure
+                                .as_ref()
+                                .map(|enclosure| enclosure.url.as_str()))
+                            .or(item.link.as_deref())
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(RssSubscription {
+            url: rss.url.clone(),
+            items: rss_items,
+            unparsed,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TorznabRss {
+    channel: TorznabChannel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TorznabChannel {
+    #[serde(default)]
+    item: Vec<TorznabItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TorznabItem {
+    title: String,
+    #[serde(default)]
+    guid: Option<String>,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+    #[serde(default)]
+    enclosure: Option<TorznabEnclosure>,
+    #[serde(default)]
+    attr: Vec<TorznabAttr>,
+}
+
+impl TorznabItem {
+    /// The value of the first `torznab:attr` with the name.
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attr
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// The values of all the `torznab:attr` with the name, e.g. the categories.
+    fn attrs(&self, name: &str) -> Vec<&str> {
+        self.attr
+            .iter()
+            .filter(|attr| attr.name == name)
+            .map(|attr| attr.value.trim())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TorznabEnclosure {
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct TorznabAttr {
+    name: String,
+    value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::RssType;
+    use crate::test::{serve_http, StubResponse};
+    use std::fs::read_to_string;
+
+    fn torznab_rss(url: &str) -> Rss {
+        Rss::builder()
+            .url(url.to_string())
+            .rss_type(RssType::Torznab)
+            .build()
+    }
+
+    #[test]
+    fn test_parse_rss_content() {
+        let rss_content = read_to_string("./tests/dataset/torznab-1.xml").unwrap();
+
+        let parser = TorznabParser::new();
+        let res = parser
+            .parse_content(&torznab_rss("http://127.0.0.1:9117/torznab"), &rss_content)
+            .unwrap();
+
+        assert_eq!(res.items.len(), 2);
+
+        let item = &res.items[0];
+        assert_eq!(item.url, "https://nyaa.si/view/1771374");
+        assert_eq!(item.title, "Sousou no Frieren");
+        assert_eq!(item.episode, 18);
+        assert_eq!(item.fansub, "[SubsPlease]");
+        assert_eq!(item.size, Some(1503238553));
+        assert_eq!(item.seeders, Some(1874));
+        assert_eq!(item.feed_category, "5070,127720");
+        assert_eq!(item.category, "");
+        assert_eq!(
+            item.torrent.url,
+            "http://127.0.0.1:9117/dl/nyaasi/?jackett_apikey=secret&path=Q2ZESjhC&file=Frieren+18"
+        );
+        assert_eq!(item.torrent.hash.as_deref(), Some("7f2b35c2a8e5b4a4d6f1e2c3b4a5968778695a4b"));
+
+        let item = &res.items[1];
+        assert_eq!(item.episode, 17);
+        assert_eq!(item.fansub, "[Erai-raws]");
+        assert_eq!(item.size, Some(1453921124));
+        assert_eq!(item.seeders, Some(96));
+        assert_eq!(item.feed_category, "5070");
+        assert_eq!(
+            item.torrent.url,
+            "magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567&dn=Frieren+17"
+        );
+        assert_eq!(item.torrent.hash, None);
+
+        // The categories of the indexer don't become the category of the downloads.
+        let rss = Rss {
+            category: Some("Anime".to_string()),
+            ..torznab_rss("http://127.0.0.1:9117/torznab")
+        };
+        let res = parser.parse_content(&rss, &rss_content).unwrap();
+        assert_eq!(res.items[0].category, "Anime");
+        assert_eq!(res.items[0].feed_category, "5070,127720");
+    }
+
+    #[test]
+    fn test_parse_empty_enclosure() {
+        let rss_content = r#"<rss version="1.0" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+  <channel>
+    <item>
+      <title>[SubsPlease] Sousou no Frieren - 18 (1080p) [C3C9B8A4].mkv</title>
+      <link>http://127.0.0.1:9117/dl/nyaasi/?file=Frieren+18</link>
+      <enclosure url="" length="1503238553" type="application/x-bittorrent" />
+    </item>
+    <item>
+      <title>[Erai-raws] Sousou no Frieren - 17 [1080p][Multiple Subtitle]</title>
+      <enclosure url=" " type="application/x-bittorrent" />
+      <torznab:attr name="magneturl" value="magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567" />
+    </item>
+  </channel>
+</rss>"#;
+
+        let res = TorznabParser::new()
+            .parse_content(&torznab_rss("http://127.0.0.1:9117/torznab"), rss_content)
+            .unwrap();
+        assert!(res.unparsed.is_empty());
+        assert_eq!(res.items[0].torrent.url, "http://127.0.0.1:9117/dl/nyaasi/?file=Frieren+18");
+        assert_eq!(
+            res.items[1].torrent.url,
+            "magnet:?xt=urn:btih:0a1b2c3d4e5f60718293a4b5c6d7e8f901234567"
+        );
+    }
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            search_url("http://127.0.0.1:9117/api?apikey=secret&t=tvsearch", "Sousou no Frieren")
+                .unwrap(),
+            "http://127.0.0.1:9117/api?apikey=secret&t=search&q=Sousou+no+Frieren"
+        );
+        assert!(search_url("not a url", "Frieren").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let rss_content = read_to_string("./tests/dataset/torznab-1.xml").unwrap();
+        let (base_url, requests) = serve_http(vec![StubResponse::ok(&rss_content)]).await;
+
+        let rss = torznab_rss(&format!(
+            "{}/api/v2.0/indexers/all/results/torznab/api?apikey=secret",
+            base_url
+        ));
+        let res = search(&rss, "Frieren").await.unwrap();
+        assert_eq!(res.items.len(), 2);
+
+        let requests = requests.await.unwrap();
+        assert!(requests[0].starts_with(
+            "GET /api/v2.0/indexers/all/results/torznab/api?apikey=secret&t=search&q=Frieren "
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rss::RssSubscriptionItem;
+
+/// The preferences to score the releases of an episode, stored in the database and
+/// shared by all the rss, like the global filters.
+///
+/// The scores pick the best release of an episode in the wait window of an rss, and
+/// the releases worth downloading again under [`crate::rss::DuplicatePolicy::PreferHigherScore`].
+/// The blocked fansubs are rejected on every rss not skipping the global filters,
+/// with a wait window or not.
+///
+/// The criteria are compared in order: fansub, resolution, subtitle, then codec.
+/// Each list is ordered from the most preferred, and values not in a list score
+/// lower than any listed one.
+///
+/// - `fansubs`: preferred fansubs, e.g. `LoliHouse,SweetSub`
+/// - `blocked_fansubs`: fansubs never downloaded, e.g. `NC-Raws`
+/// - `resolutions`: default to `2160p,1080p,720p,480p`
+/// - `subtitles`: subtitle types made of `chs`, `cht`, `jpn`, `eng`, `soft` and `hard`,
+///   joined by `+` when all of them are required, e.g. `chs+soft,chs,cht`
+/// - `codecs`: `hevc`, `avc` or `av1`, e.g. `hevc,avc`
+///
+/// Fansubs match case-insensitively by substring, so `LoliHouse` also matches
+/// `[喵萌奶茶屋&LoliHouse]`. The others match the parsed media info of the release,
+/// see [`crate::rss::media_info::MediaInfo`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreConfig {
+    pub fansubs: Vec<String>,
+    pub blocked_fansubs: Vec<String>,
+    pub resolutions: Vec<String>,
+    pub subtitles: Vec<String>,
+    pub codecs: Vec<String>,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            fansubs: vec![],
+            blocked_fansubs: vec![],
+            resolutions: parse_list("2160p,1080p,720p,480p"),
+            subtitles: vec![],
+            codecs: vec![],
+        }
+    }
+}
+
+impl ScoreConfig {
+    /// Whether the fansub of the item is blocked.
+    pub fn is_blocked(&self, item: &RssSubscriptionItem) -> bool {
+        let fansub = item.fansub.to_lowercase();
+        self.blocked_fansubs
+            .iter()
+            .any(|blocked| fansub.contains(blocked.as_str()))
+    }
+
+    /// The score of the item, the higher the better.
+    pub fn score(&self, item: &RssSubscriptionItem) -> i64 {
+        let fansub = item.fansub.to_lowercase();
+        let media_info = item.parsed_media_info();
+
+        let ranks = [
+            rank(&self.fansubs, |preferred| fansub.contains(preferred)),
+            rank(&self.resolutions, |resolution| {
+                media_info
+                    .resolution
+                    .is_some_and(|height| format!("{}p", height) == resolution)
+            }),
+            rank(&self.subtitles, |subtitle| {
+                subtitle.split('+').all(|tag| media_info.has_subtitle(tag))
+            }),
+            rank(&self.codecs, |codec| {
+                media_info
+                    .video_codec
+                    .is_some_and(|video_codec| video_codec.to_string().eq_ignore_ascii_case(codec))
+            }),
+        ];
+        ranks.iter().fold(0, |score, rank| score * 100 + rank)
+    }
+}
+
+/// Parse lowercase values separated by commas.
+pub fn parse_list(content: &str) -> Vec<String> {
+    content
+        .split(',')
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// The rank of the first matched value, from the length of the list down to 1,
+/// or 0 if none matches. Capped at 99 to keep the criteria apart in the score.
+fn rank(list: &[String], is_match: impl Fn(&str) -> bool) -> i64 {
+    match list.iter().position(|value| is_match(value)) {
+        Some(index) => (list.len() - index).min(99) as i64,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gen_item(fansub: &str, media_info: &str) -> RssSubscriptionItem {
+        RssSubscriptionItem {
+            fansub: fansub.to_string(),
+            media_info: media_info.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        let config = ScoreConfig {
+            fansubs: parse_list("LoliHouse, SweetSub"),
+            blocked_fansubs: parse_list("NC-Raws"),
+            subtitles: parse_list("chs+soft,chs"),
+            codecs: parse_list("hevc,avc"),
+            ..Default::default()
+        };
+
+        let best =
+            gen_item("[喵萌奶茶屋&LoliHouse]", "[WebRip 1080p HEVC-10bit AAC][简繁内封字幕]");
+        let hardsub = gen_item("[LoliHouse]", "[WebRip 1080p HEVC-10bit AAC][简体内嵌]");
+        let avc = gen_item("[LoliHouse]", "[WebRip 1080p AVC AAC][简繁内封字幕]");
+        let second_fansub = gen_item("[SweetSub]", "[WebRip 2160p HEVC-10bit AAC][简繁内封字幕]");
+        let unknown = gen_item("[Up to 21°C]", "(CR 1920x1080 AVC AAC MKV)");
+        assert_eq!(config.score(&best), 2030202);
+
+        let scores: Vec<_> = [&best, &avc, &hardsub, &second_fansub, &unknown]
+            .iter()
+            .map(|item| config.score(item))
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", scores);
+
+        assert!(config.is_blocked(&gen_item("[NC-Raws]", "(B-Global 1920x1080 HEVC AAC MKV)")));
+        assert!(!config.is_blocked(&best));
+    }
+}
@@ -1,15 +1,32 @@
 use crate::{
     get_pool,
-    rss::{Rss, RssType},
-    tx_begin,
+    rss::{
+        decision::{DecisionReason, ItemDecision},
+        health::{RssHealth, MAX_CONSECUTIVE_FAILURES},
+        ledger::{RssItemDecision, RssItemRecord},
+        score::{parse_list, ScoreConfig},
+        DuplicatePolicy, HttpCache, Rss, RssSubscriptionItem, RssType, UnparsedItem,
+    },
+    tx_begin, BTResult,
 };
+use chrono::{DateTime, Local};
 use log::info;
 use sqlx::query;
+use std::collections::HashMap;
 use std::str::FromStr;
 
-use super::filter::RssFilterChain;
+use super::filter::{GlobalFilter, RssFilter, RssFilterChain};
 
-pub async fn add_rss(info: &Rss) -> Result<i64, sqlx::Error> {
+/// Reject the rss if any of its filters is invalid.
+fn validate_filters(rss: &Rss) -> BTResult<()> {
+    if let Some(filters) = &rss.filters {
+        filters.validate()?;
+    }
+    Ok(())
+}
+
+pub async fn add_rss(info: &Rss) -> BTResult<i64> {
+    validate_filters(info)?;
     let tx = tx_begin().await?;
 
     let id = match check_repeat_by_url(&info.url).await? {
@@ -49,7 +66,7 @@ fn serialize_filters(rss_filters: &Option<RssFilterChain>) -> Option<String> {
 
 fn deserialize_filters(filters_str: &Option<String>) -> Option<RssFilterChain> {
     match filters_str {
-        Some(filters_str) => Some(serde_json::from_str(filters_str).unwrap()),
+        Some(filters_str) => Some(RssFilterChain::from_stored(filters_str)),
         None => None,
     }
 }
@@ -58,10 +75,13 @@ pub async fn insert_rss(rss: &Rss) -> Result<i64, sqlx::Error> {
     let rss_type = rss.rss_type.to_string();
     let season = rss.season.map(|s| s as i64);
     let filters = serialize_filters(&rss.filters);
+    let wait_minutes = rss.wait_minutes.map(|m| m as i64);
+    let duplicate_policy = rss.duplicate_policy.to_string();
     let id = query!(
         r#"
-INSERT INTO main.rss (url, title, rss_type, enabled, season, filters, description, category)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+INSERT INTO main.rss (url, title, rss_type, enabled, season, filters, description, category, skip_global_filters,
+    wait_minutes, duplicate_policy, skip_episode_titles)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
         "#,
         rss.url,
         rss.title,
@@ -71,6 +91,10 @@ VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
         filters,
         rss.description,
         rss.category,
+        rss.skip_global_filters,
+        wait_minutes,
+        duplicate_policy,
+        rss.skip_episode_titles,
     )
     .execute(&get_pool().await)
     .await?
@@ -96,13 +120,15 @@ WHERE id = ?1
 pub async fn query_rss() -> Result<Vec<Rss>, sqlx::Error> {
     let recs = query!(
         r#"
-SELECT id, url, title, rss_type, enabled, season, filters, description, category
+SELECT id, url, title, rss_type, enabled, season, filters, description, category, etag, last_modified,
+       skip_global_filters, wait_minutes, duplicate_policy, skip_episode_titles
 FROM main.rss
 ORDER BY enabled DESC, title ASC, season ASC
         "#,
     )
     .fetch_all(&get_pool().await)
     .await?;
+    let mut health = query_rss_health().await?;
 
     Ok(recs
         .into_iter()
@@ -114,21 +140,48 @@ ORDER BY enabled DESC, title ASC, season ASC
             enabled: rec.enabled.map(|e| e == 1),
             season: rec.season.map(|s| s as u64),
             filters: deserialize_filters(&rec.filters),
+            skip_global_filters: rec.skip_global_filters == 1,
+            wait_minutes: rec.wait_minutes.map(|m| m as u64),
+            duplicate_policy: DuplicatePolicy::from_str(&rec.duplicate_policy).unwrap(),
+            skip_episode_titles: rec.skip_episode_titles == 1,
             description: rec.description,
             category: rec.category,
+            http_cache: HttpCache {
+                etag: rec.etag,
+                last_modified: rec.last_modified,
+            },
+            health: health.remove(&rec.id),
         })
         .collect())
 }
 
-pub async fn update_rss(id: i64, rss: &Rss) -> Result<(), sqlx::Error> {
+pub async fn get_rss(id: i64) -> Result<Option<Rss>, sqlx::Error> {
+    Ok(query_rss()
+        .await?
+        .into_iter()
+        .find(|rss| rss.id == Some(id)))
+}
+
+pub async fn update_rss(id: i64, rss: &Rss) -> BTResult<()> {
+    validate_filters(rss)?;
     let rss_type = rss.rss_type.to_string();
     let season = rss.season.map(|s| s as i64);
     let filters = serialize_filters(&rss.filters);
+    let wait_minutes = rss.wait_minutes.map(|m| m as i64);
+    let duplicate_policy = rss.duplicate_policy.to_string();
+    let mut tx = tx_begin().await?;
+
+    let old_url = query!(r#"SELECT url FROM main.rss WHERE id = ?1"#, id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|rec| rec.url);
+
     query!(
         r#"
 UPDATE main.rss
-SET url = ?1, title = ?2, rss_type = ?3, enabled = ?4, season = ?5, filters = ?6, description = ?7, category = ?8
-WHERE id = ?9
+SET url = ?1, title = ?2, rss_type = ?3, enabled = ?4, season = ?5, filters = ?6, description = ?7, category = ?8,
+    skip_global_filters = ?9, wait_minutes = ?10, duplicate_policy = ?11, skip_episode_titles = ?12
+WHERE id = ?13
         "#,
         rss.url,
         rss.title,
@@ -138,18 +191,490 @@ WHERE id = ?9
         filters,
         rss.description,
         rss.category,
+        rss.skip_global_filters,
+        wait_minutes,
+        duplicate_policy,
+        rss.skip_episode_titles,
+        id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // The filters may be changed, decide the filtered items again.
+    let pending = RssItemDecision::Pending.to_string();
+    let filtered = RssItemDecision::Filtered.to_string();
+    let reset = query!(
+        r#"
+UPDATE main.rss_item
+SET decision = ?1
+WHERE rss_id = ?2 AND decision = ?3
+        "#,
+        pending,
+        id,
+        filtered,
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // A not modified feed isn't decided again, and the validators of another url are
+    // meaningless, so fetch the feed in full next time.
+    if reset > 0 || old_url.is_some_and(|old_url| old_url != rss.url) {
+        query!(
+            r#"
+UPDATE main.rss
+SET etag = NULL, last_modified = NULL
+WHERE id = ?1
+            "#,
+            id,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Give an auto disabled rss a fresh start when it's enabled again.
+    if rss.enabled == Some(true) {
+        query!(
+            r#"
+UPDATE main.rss_health
+SET consecutive_failures = 0
+WHERE rss_id = ?1 AND consecutive_failures >= ?2
+            "#,
+            id,
+            MAX_CONSECUTIVE_FAILURES,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn update_http_cache(id: i64, http_cache: &HttpCache) -> Result<(), sqlx::Error> {
+    query!(
+        r#"
+UPDATE main.rss
+SET etag = ?1, last_modified = ?2
+WHERE id = ?3
+        "#,
+        http_cache.etag,
+        http_cache.last_modified,
+        id,
+    )
+    .execute(&get_pool().await)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn disable_rss(id: i64) -> Result<(), sqlx::Error> {
+    query!(
+        r#"
+UPDATE main.rss
+SET enabled = 0
+WHERE id = ?1
+        "#,
+        id,
+    )
+    .execute(&get_pool().await)
+    .await?;
+
+    Ok(())
+}
+
+fn parse_time(time: &str) -> DateTime<Local> {
+    DateTime::parse_from_rfc3339(time).unwrap().into()
+}
+
+async fn query_rss_health() -> Result<HashMap<i64, RssHealth>, sqlx::Error> {
+    let recs = query!(
+        r#"
+SELECT rss_id, last_attempt, last_success, consecutive_failures, last_error, item_count
+FROM main.rss_health
+        "#,
+    )
+    .fetch_all(&get_pool().await)
+    .await?;
+
+    Ok(recs
+        .into_iter()
+        .map(|rec| {
+            let health = RssHealth {
+                last_attempt: parse_time(&rec.last_attempt),
+                last_success: rec.last_success.as_deref().map(parse_time),
+                consecutive_failures: rec.consecutive_failures as u32,
+                last_error: rec.last_error,
+                item_count: rec.item_count.map(|c| c as u64),
+            };
+            (rec.rss_id, health)
+        })
+        .collect())
+}
+
+/// Record a successful fetch of the rss.
+///
+/// `item_count` is `None` if the content is not modified since the last fetch.
+pub async fn record_fetch_success(id: i64, item_count: Option<u64>) -> Result<(), sqlx::Error> {
+    let now = Local::now().to_rfc3339();
+    let item_count = item_count.map(|c| c as i64);
+    query!(
+        r#"
+INSERT INTO main.rss_health (rss_id, last_attempt, last_success, consecutive_failures, last_error, item_count)
+VALUES (?1, ?2, ?2, 0, NULL, ?3)
+ON CONFLICT (rss_id) DO UPDATE
+SET last_attempt = excluded.last_attempt, last_success = excluded.last_success,
+    consecutive_failures = 0, last_error = NULL, item_count = COALESCE(excluded.item_count, item_count)
+        "#,
+        id,
+        now,
+        item_count,
+    )
+    .execute(&get_pool().await)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed fetch of the rss, and return the number of consecutive failures.
+pub async fn record_fetch_failure(id: i64, error: &str) -> Result<u32, sqlx::Error> {
+    let now = Local::now().to_rfc3339();
+    let rec = query!(
+        r#"
+INSERT INTO main.rss_health (rss_id, last_attempt, consecutive_failures, last_error)
+VALUES (?1, ?2, 1, ?3)
+ON CONFLICT (rss_id) DO UPDATE
+SET last_attempt = excluded.last_attempt, consecutive_failures = consecutive_failures + 1,
+    last_error = excluded.last_error
+RETURNING consecutive_failures
+        "#,
         id,
+        now,
+        error,
+    )
+    .fetch_one(&get_pool().await)
+    .await?;
+
+    Ok(rec.consecutive_failures as u32)
+}
+
+/// Record that the item is seen in the rss, and return the last decision about it.
+pub async fn mark_item_seen(
+    rss_id: i64,
+    item: &RssSubscriptionItem,
+) -> Result<RssItemDecision, sqlx::Error> {
+    let guid = item.guid();
+    let season = item.season as i64;
+    let episode = item.episode as i64;
+    let now = Local::now().to_rfc3339();
+    let pending = RssItemDecision::Pending.to_string();
+    let rec = query!(
+        r#"
+INSERT INTO main.rss_item (rss_id, guid, title, season, episode, torrent_url, first_seen, last_seen, decision)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7, ?8)
+ON CONFLICT (rss_id, guid) DO UPDATE
+SET last_seen = excluded.last_seen
+RETURNING decision
+        "#,
+        rss_id,
+        guid,
+        item.title,
+        season,
+        episode,
+        item.torrent.url,
+        now,
+        pending,
+    )
+    .fetch_one(&get_pool().await)
+    .await?;
+
+    Ok(RssItemDecision::from_str(&rec.decision).unwrap())
+}
+
+/// Record that the item failed to be parsed is seen in the rss, and return the last
+/// decision about it. The season and episode are unknown and kept as 0.
+pub async fn mark_unparsed_item_seen(
+    rss_id: i64,
+    item: &UnparsedItem,
+) -> Result<RssItemDecision, sqlx::Error> {
+    let now = Local::now().to_rfc3339();
+    let pending = RssItemDecision::Pending.to_string();
+    let rec = query!(
+        r#"
+INSERT INTO main.rss_item (rss_id, guid, title, season, episode, torrent_url, first_seen, last_seen, decision)
+VALUES (?1, ?2, ?3, 0, 0, '', ?4, ?4, ?5)
+ON CONFLICT (rss_id, guid) DO UPDATE
+SET last_seen = excluded.last_seen
+RETURNING decision
+        "#,
+        rss_id,
+        item.guid,
+        item.title,
+        now,
+        pending,
+    )
+    .fetch_one(&get_pool().await)
+    .await?;
+
+    Ok(RssItemDecision::from_str(&rec.decision).unwrap())
+}
+
+/// Record the decision about the item, if it changes the state of the item in the ledger.
+pub async fn update_item_decision(rss_id: i64, record: &ItemDecision) -> Result<(), sqlx::Error> {
+    let (Some(guid), Some(decision)) = (&record.guid, record.reason.ledger_decision()) else {
+        return Ok(());
+    };
+
+    let decision = decision.to_string();
+    let reason = record.reason.to_string();
+    let filter = record
+        .filter
+        .as_ref()
+        .map(|filter| serde_json::to_string(filter).unwrap());
+    query!(
+        r#"
+UPDATE main.rss_item
+SET decision = ?1, reason = ?2, filter = ?3, detail = ?4
+WHERE rss_id = ?5 AND guid = ?6
+        "#,
+        decision,
+        reason,
+        filter,
+        record.detail,
+        rss_id,
+        guid,
+    )
+    .execute(&get_pool().await)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_item_decision(
+    rss_id: i64,
+    guid: &str,
+) -> Result<Option<RssItemDecision>, sqlx::Error> {
+    let rec = query!(
+        r#"
+SELECT decision
+FROM main.rss_item
+WHERE rss_id = ?1 AND guid = ?2
+        "#,
+        rss_id,
+        guid,
+    )
+    .fetch_optional(&get_pool().await)
+    .await?;
+
+    Ok(rec.map(|rec| RssItemDecision::from_str(&rec.decision).unwrap()))
+}
+
+/// Query the items seen in the rss, the latest first.
+pub async fn query_rss_items(rss_id: i64) -> Result<Vec<RssItemRecord>, sqlx::Error> {
+    let recs = query!(
+        r#"
+SELECT id, rss_id, guid, title, season, episode, torrent_url, first_seen, last_seen, decision,
+    reason, filter, detail
+FROM main.rss_item
+WHERE rss_id = ?1
+ORDER BY first_seen DESC, id DESC
+        "#,
+        rss_id,
+    )
+    .fetch_all(&get_pool().await)
+    .await?;
+
+    Ok(recs
+        .into_iter()
+        .map(|rec| RssItemRecord {
+            id: rec.id,
+            rss_id: rec.rss_id,
+            guid: rec.guid,
+            title: rec.title,
+            season: rec.season as u64,
+            episode: rec.episode as u64,
+            torrent_url: rec.torrent_url,
+            first_seen: parse_time(&rec.first_seen),
+            last_seen: parse_time(&rec.last_seen),
+            decision: RssItemDecision::from_str(&rec.decision).unwrap(),
+            reason: rec
+                .reason
+                .as_deref()
+                .map(|reason| DecisionReason::from_str(reason).unwrap()),
+            filter: rec
+                .filter
+                .as_deref()
+                .and_then(|filter| serde_json::from_str::<String>(filter).ok())
+                .map(|filter| RssFilter::from_stored(&filter)),
+            detail: rec.detail,
+        })
+        .collect())
+}
+
+pub async fn add_global_filter(global_filter: &GlobalFilter) -> BTResult<i64> {
+    RssFilterChain(vec![global_filter.filter.clone()]).validate()?;
+    let filter = global_filter.filter.to_string();
+    let id = query!(
+        r#"
+INSERT INTO main.global_filter (filter, description)
+VALUES (?1, ?2)
+        "#,
+        filter,
+        global_filter.description,
     )
     .execute(&get_pool().await)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+pub async fn delete_global_filter(id: i64) -> Result<(), sqlx::Error> {
+    let mut tx = tx_begin().await?;
+    query!(
+        r#"
+DELETE FROM main.global_filter
+WHERE id = ?1
+        "#,
+        id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // The items filtered by it may be accepted now, decide them again. A not modified
+    // feed isn't decided again, so fetch their feeds in full next time.
+    let pending = RssItemDecision::Pending.to_string();
+    let filtered = RssItemDecision::Filtered.to_string();
+    query!(
+        r#"
+UPDATE main.rss
+SET etag = NULL, last_modified = NULL
+WHERE id IN (SELECT DISTINCT rss_id FROM main.rss_item WHERE decision = ?1)
+        "#,
+        filtered,
+    )
+    .execute(&mut *tx)
+    .await?;
+    query!(
+        r#"
+UPDATE main.rss_item
+SET decision = ?1
+WHERE decision = ?2
+        "#,
+        pending,
+        filtered,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn query_global_filters() -> Result<Vec<GlobalFilter>, sqlx::Error> {
+    let recs = query!(
+        r#"
+SELECT id, filter, description
+FROM main.global_filter
+ORDER BY id ASC
+        "#,
+    )
+    .fetch_all(&get_pool().await)
+    .await?;
+
+    Ok(recs
+        .into_iter()
+        .map(|rec| GlobalFilter {
+            id: Some(rec.id),
+            filter: RssFilter::from_stored(&rec.filter),
+            description: rec.description,
+        })
+        .collect())
+}
+
+/// The score config shared by all the rss, the default one if it's never set.
+pub async fn get_score_config() -> Result<ScoreConfig, sqlx::Error> {
+    let rec = query!(
+        r#"
+SELECT fansubs, blocked_fansubs, resolutions, subtitles, codecs
+FROM main.score_config
+WHERE id = 1
+        "#,
+    )
+    .fetch_optional(&get_pool().await)
+    .await?;
+
+    Ok(match rec {
+        Some(rec) => ScoreConfig {
+            fansubs: parse_list(&rec.fansubs),
+            blocked_fansubs: parse_list(&rec.blocked_fansubs),
+            resolutions: parse_list(&rec.resolutions),
+            subtitles: parse_list(&rec.subtitles),
+            codecs: parse_list(&rec.codecs),
+        },
+        None => ScoreConfig::default(),
+    })
+}
+
+pub async fn update_score_config(config: &ScoreConfig) -> Result<(), sqlx::Error> {
+    let fansubs = config.fansubs.join(",");
+    let blocked_fansubs = config.blocked_fansubs.join(",");
+    let resolutions = config.resolutions.join(",");
+    let subtitles = config.subtitles.join(",");
+    let codecs = config.codecs.join(",");
+    let mut tx = tx_begin().await?;
+    query!(
+        r#"
+INSERT INTO main.score_config (id, fansubs, blocked_fansubs, resolutions, subtitles, codecs)
+VALUES (1, ?1, ?2, ?3, ?4, ?5)
+ON CONFLICT (id) DO UPDATE
+SET fansubs = excluded.fansubs, blocked_fansubs = excluded.blocked_fansubs,
+    resolutions = excluded.resolutions, subtitles = excluded.subtitles, codecs = excluded.codecs
+        "#,
+        fansubs,
+        blocked_fansubs,
+        resolutions,
+        subtitles,
+        codecs,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // The blocked fansubs may be changed, decide the blocked items again. A not modified
+    // feed isn't decided again, so fetch their feeds in full next time.
+    let pending = RssItemDecision::Pending.to_string();
+    let blocked = DecisionReason::Blocked.to_string();
+    query!(
+        r#"
+UPDATE main.rss
+SET etag = NULL, last_modified = NULL
+WHERE id IN (SELECT DISTINCT rss_id FROM main.rss_item WHERE reason = ?1)
+        "#,
+        blocked,
+    )
+    .execute(&mut *tx)
+    .await?;
+    query!(
+        r#"
+UPDATE main.rss_item
+SET decision = ?1
+WHERE reason = ?2
+        "#,
+        pending,
+        blocked,
+    )
+    .execute(&mut *tx)
     .await?;
 
+    tx.commit().await?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::init;
+    use crate::rss::filter::FilterRegex;
+    use crate::{init, BTError};
 
     #[tokio::test]
     async fn test_rss() {
@@ -179,6 +704,7 @@ mod tests {
         assert_eq!(rss_list[0].season, rss.season);
         assert_eq!(rss_list[0].enabled, rss.enabled);
         assert_eq!(rss_list[0].title, rss.title);
+        assert_eq!(rss_list[0].duplicate_policy, DuplicatePolicy::Always);
 
         rss.title = Some("Frieren: Beyond Journey's End".to_string());
         assert_eq!(add_rss(&rss).await.unwrap(), id);
@@ -188,9 +714,190 @@ mod tests {
         let rss_list = query_rss().await.unwrap();
         assert_eq!(rss_list.len(), 1);
         assert_eq!(rss_list[0].title, rss.title);
+        assert_eq!(rss_list[0].http_cache, HttpCache::default());
+
+        let http_cache = HttpCache {
+            etag: Some("W/\"5f3c\"".to_string()),
+            last_modified: Some("Fri, 19 Jan 2024 16:01:55 GMT".to_string()),
+        };
+        update_http_cache(id, &http_cache).await.unwrap();
+        // Updating the url resets the cache of the old feed.
+        let old_url = rss.url.clone();
+        rss.url = "https://mikanani.me/RSS/Bangumi?bangumiId=3141".to_string();
+        update_rss(id, &rss).await.unwrap();
+        let rss_list = query_rss().await.unwrap();
+        assert_eq!(rss_list[0].http_cache, HttpCache::default());
+        assert_eq!(rss_list[0].health, None);
+        rss.url = old_url;
+        update_rss(id, &rss).await.unwrap();
+
+        record_fetch_success(id, Some(12)).await.unwrap();
+        assert_eq!(record_fetch_failure(id, "timeout").await.unwrap(), 1);
+        assert_eq!(record_fetch_failure(id, "502 Bad Gateway").await.unwrap(), 2);
+        let health = query_rss().await.unwrap()[0].health.clone().unwrap();
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.last_error.as_deref(), Some("502 Bad Gateway"));
+        assert_eq!(health.item_count, Some(12));
+        assert!(health.last_success.unwrap() <= health.last_attempt);
+
+        // A not modified feed keeps the item count.
+        record_fetch_success(id, None).await.unwrap();
+        let health = query_rss().await.unwrap()[0].health.clone().unwrap();
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.last_error, None);
+        assert_eq!(health.item_count, Some(12));
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            record_fetch_failure(id, "timeout").await.unwrap();
+        }
+        disable_rss(id).await.unwrap();
+        assert_eq!(query_rss().await.unwrap()[0].enabled, Some(false));
+
+        // Enabling it again resets the failures.
+        update_rss(id, &rss).await.unwrap();
+        let rss_list = query_rss().await.unwrap();
+        assert_eq!(rss_list[0].enabled, Some(true));
+        assert_eq!(rss_list[0].health.as_ref().unwrap().consecutive_failures, 0);
+
+        let item = RssSubscriptionItem::builder()
+            .url("https://mikanani.me/Home/Episode/059724511d60173251b378b04709aceff92fffb5".to_string())
+            .title("Sousou no Frieren".to_string())
+            .episode_title("".to_string())
+            .season(1)
+            .episode(18)
+            .fansub("[LoliHouse]".to_string())
+            .media_info("".to_string())
+            .torrent(
+                crate::downloader::TorrentMeta::builder()
+                    .url("https://mikanani.me/Download/20240119/059724511d60173251b378b04709aceff92fffb5.torrent".to_string())
+                    .build(),
+            )
+            .category("".to_string())
+            .build();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Pending);
+        let filter = RssFilter::FilenameRegex(FilterRegex::new("Baha").unwrap());
+        let record = ItemDecision {
+            title: item.title.clone(),
+            guid: Some(item.guid().to_string()),
+            season: Some(1),
+            episode: Some(18),
+            accepted: false,
+            reason: DecisionReason::Filtered,
+            filter: Some(filter.clone()),
+            score: None,
+            detail: None,
+        };
+        update_item_decision(id, &record).await.unwrap();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Filtered);
+        assert_eq!(
+            get_item_decision(id, item.guid()).await.unwrap(),
+            Some(RssItemDecision::Filtered)
+        );
+        assert_eq!(get_rss(id).await.unwrap().unwrap().url, rss.url);
+
+        let items = query_rss_items(id).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].guid, item.url);
+        assert_eq!(items[0].episode, 18);
+        assert_eq!(items[0].decision, RssItemDecision::Filtered);
+        assert_eq!(items[0].reason, Some(DecisionReason::Filtered));
+        assert_eq!(items[0].filter, Some(filter.clone()));
+        assert!(items[0].first_seen <= items[0].last_seen);
+
+        // Updating the rss decides the filtered items again, fetching the feed in full.
+        update_http_cache(id, &http_cache).await.unwrap();
+        update_rss(id, &rss).await.unwrap();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Pending);
+        assert_eq!(get_rss(id).await.unwrap().unwrap().http_cache, HttpCache::default());
+
+        // Invalid filters are refused, and the stored ones are left unchanged.
+        let mut invalid = rss.clone();
+        invalid.filters = Some(RssFilterChain::from_stored(r#"["FilenameRegex-[1080p"]"#));
+        assert!(matches!(add_rss(&invalid).await, Err(BTError::InvalidFilter(_))));
+        assert!(matches!(update_rss(id, &invalid).await, Err(BTError::InvalidFilter(_))));
+        assert!(get_rss(id).await.unwrap().unwrap().filters.is_none());
+
+        rss.skip_global_filters = true;
+        rss.wait_minutes = Some(120);
+        rss.duplicate_policy = DuplicatePolicy::PreferHigherScore;
+        rss.skip_episode_titles = true;
+        update_rss(id, &rss).await.unwrap();
+        let stored = get_rss(id).await.unwrap().unwrap();
+        assert!(stored.skip_global_filters);
+        assert_eq!(stored.wait_minutes, Some(120));
+        assert_eq!(stored.duplicate_policy, DuplicatePolicy::PreferHigherScore);
+        assert!(stored.skip_episode_titles);
+
+        let global_filter = GlobalFilter::builder()
+            .filter(filter.clone())
+            .description(Some("never Baha".to_string()))
+            .build();
+        let filter_id = add_global_filter(&global_filter).await.unwrap();
+        assert_eq!(
+            query_global_filters().await.unwrap(),
+            vec![GlobalFilter {
+                id: Some(filter_id),
+                ..global_filter
+            }]
+        );
+        let invalid = GlobalFilter::builder()
+            .filter(RssFilter::from_stored("FilenameRegex-[1080p"))
+            .build();
+        assert!(matches!(add_global_filter(&invalid).await, Err(BTError::InvalidFilter(_))));
+
+        // Removing a global filter decides the filtered items again.
+        update_item_decision(id, &record).await.unwrap();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Filtered);
+        update_http_cache(id, &http_cache).await.unwrap();
+        delete_global_filter(filter_id).await.unwrap();
+        assert!(query_global_filters().await.unwrap().is_empty());
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Pending);
+        assert_eq!(get_rss(id).await.unwrap().unwrap().http_cache, HttpCache::default());
+
+        // Updating the score config decides the blocked items again.
+        assert_eq!(get_score_config().await.unwrap(), ScoreConfig::default());
+        let blocked = ItemDecision {
+            reason: DecisionReason::Blocked,
+            filter: None,
+            ..record.clone()
+        };
+        update_item_decision(id, &blocked).await.unwrap();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Filtered);
+        update_http_cache(id, &http_cache).await.unwrap();
+        let score_config = ScoreConfig {
+            fansubs: vec!["LoliHouse".to_string()],
+            blocked_fansubs: vec!["NC-Raws".to_string()],
+            ..Default::default()
+        };
+        update_score_config(&score_config).await.unwrap();
+        assert_eq!(mark_item_seen(id, &item).await.unwrap(), RssItemDecision::Pending);
+        assert_eq!(get_rss(id).await.unwrap().unwrap().http_cache, HttpCache::default());
+        let stored = get_score_config().await.unwrap();
+        assert_eq!(stored.fansubs, ["lolihouse"]);
+        assert_eq!(stored.blocked_fansubs, ["nc-raws"]);
+        assert_eq!(stored.resolutions, ScoreConfig::default().resolutions);
+        update_score_config(&ScoreConfig::default()).await.unwrap();
+
+        // The items failed to be parsed are recorded, and retried on the next poll.
+        let unparsed = UnparsedItem {
+            title: "[LoliHouse] 招募翻译校对".to_string(),
+            guid: "https://mikanani.me/Home/Episode/unparsed".to_string(),
+            error: "Unrecognized episode".to_string(),
+        };
+        assert_eq!(mark_unparsed_item_seen(id, &unparsed).await.unwrap(), RssItemDecision::Pending);
+        update_item_decision(id, &ItemDecision::unparsed(&unparsed))
+            .await
+            .unwrap();
+        assert_eq!(mark_unparsed_item_seen(id, &unparsed).await.unwrap(), RssItemDecision::Failed);
+        let items = query_rss_items(id).await.unwrap();
+        let record = items.iter().find(|rec| rec.guid == unparsed.guid).unwrap();
+        assert_eq!(record.title, unparsed.title);
+        assert_eq!(record.reason, Some(DecisionReason::ParseFailed));
+        assert_eq!(record.detail.as_deref(), Some("Unrecognized episode"));
 
         delete_rss(id).await.unwrap();
         let rss_list = query_rss().await.unwrap();
         assert_eq!(rss_list.len(), 0);
+        assert_eq!(query_rss_items(id).await.unwrap().len(), 0);
     }
 }
@@ -10,6 +10,28 @@ pub fn gen_torrent_with_custom_filename(filename: &str) -> Torrent {
     Torrent::from_bytes(torrent_content.as_bytes()).unwrap()
 }
 
+/// A multi-file torrent, each file path is relative to the `name` folder.
+#[allow(unused)]
+pub fn gen_torrent_with_files(name: &str, paths: &[&str]) -> Torrent {
+    let files: String = paths
+        .iter()
+        .map(|path| {
+            let parts: String = path
+                .split('/')
+                .map(|part| format!("{}:{}", part.len(), part))
+                .collect();
+            format!("d6:lengthi1024e4:pathl{}ee", parts)
+        })
+        .collect();
+    let torrent_content = format!(
+        "d8:announce0:4:infod5:filesl{}e4:name{}:{}12:piece lengthi0e6:pieces0:ee",
+        files,
+        name.len(),
+        name
+    );
+    Torrent::from_bytes(torrent_content.as_bytes()).unwrap()
+}
+
 #[allow(unused)]
 pub async fn get_dummy_torrent() -> TorrentMeta {
     let dot_torrent =
@@ -25,3 +47,69 @@ pub async fn get_dummy_torrent() -> TorrentMeta {
         .save_path(Some("test_save_path".to_string()))
         .build()
 }
+
+/// A canned response served by [`serve_http`].
+#[allow(unused)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[allow(unused)]
+impl StubResponse {
+    pub fn ok(body: &str) -> Self {
+        Self {
+            status: 200,
+            headers: vec![],
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Serve the responses in order on a local port, one response per connection.
+///
+/// Returns the base url, e.g. `http://127.0.0.1:12345`, and a handle resolving to
+/// the raw heads of the requests received.
+#[allow(unused)]
+pub async fn serve_http(
+    responses: Vec<StubResponse>,
+) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            requests.push(String::from_utf8_lossy(&head).to_string());
+
+            let mut raw = format!("HTTP/1.1 {} STUB\r\n", response.status);
+            for (name, value) in &response.headers {
+                raw.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            raw.push_str(&format!(
+                "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.body.len(),
+                response.body
+            ));
+            stream.write_all(raw.as_bytes()).await.unwrap();
+            stream.shutdown().await.ok();
+        }
+        requests
+    });
+
+    (base_url, handle)
+}